@@ -0,0 +1,91 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Support for choosing the collection `collect!` fills at runtime.
+*/
+use std::fmt;
+
+/**
+An object-safe stand-in for `Extend`, whose own `extend` method is generic.
+*/
+trait DynExtend<T> {
+    fn extend_dyn(&mut self, iter: &mut dyn Iterator<Item=T>);
+}
+
+impl<T, E: Extend<T>> DynExtend<T> for E {
+    fn extend_dyn(&mut self, iter: &mut dyn Iterator<Item=T>) {
+        self.extend(iter);
+    }
+}
+
+/**
+A type-erased handle to some collection which can be extended with values of type `T`.
+
+This lets the choice of collection be made at runtime: wrap a mutable borrow of whichever collection you like, then fill it using `Extend` or `collect![into ...]`.  Once the builder is dropped, the collection can be used directly again.  Size hints are passed through, so the underlying collection can still reserve space up front.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use collect_mac::CollectBuilder;
+# use std::collections::HashSet;
+# fn main() {
+let mut vec = vec![];
+let mut set = HashSet::new();
+let unique = true;
+{
+    let mut builder = if unique {
+        CollectBuilder::new(&mut set)
+    } else {
+        CollectBuilder::new(&mut vec)
+    };
+    collect![into builder; 1, 2, 2, 3];
+}
+assert_eq!(set.len(), 3);
+assert!(vec.is_empty());
+# }
+```
+*/
+pub struct CollectBuilder<'a, T> {
+    inner: Box<dyn DynExtend<T> + 'a>,
+}
+
+impl<'a, T> CollectBuilder<'a, T> {
+    /**
+    Creates a builder which extends the given collection.
+    */
+    pub fn new<E>(col: &'a mut E) -> CollectBuilder<'a, T>
+    where E: Extend<T> {
+        CollectBuilder {
+            inner: Box::new(ExtendRef(col)),
+        }
+    }
+}
+
+impl<'a, T> Extend<T> for CollectBuilder<'a, T> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        self.inner.extend_dyn(&mut iter.into_iter());
+    }
+}
+
+impl<'a, T> fmt::Debug for CollectBuilder<'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("CollectBuilder").finish()
+    }
+}
+
+/**
+Forwards `Extend` through a mutable borrow, since the standard collections don't implement it for `&mut Self`.
+*/
+struct ExtendRef<'a, E: 'a>(&'a mut E);
+
+impl<'a, T, E: Extend<T>> Extend<T> for ExtendRef<'a, E> {
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
@@ -0,0 +1,85 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Error types produced by the fallible forms of `collect!` and its helper macros.
+*/
+use std::error::Error;
+use std::fmt;
+
+/**
+The error produced by `try_collect_map!` when the same key is given more than once.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateKeyError<K> {
+    /// The first key which was found to be a duplicate.
+    pub key: K,
+}
+
+impl<K: fmt::Debug> fmt::Display for DuplicateKeyError<K> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "duplicate key: {:?}", self.key)
+    }
+}
+
+impl<K: fmt::Debug> Error for DuplicateKeyError<K> {}
+
+/**
+The error produced by `collect![parse ...]` when an element fails to parse.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError<E> {
+    /// The position of the offending element, counting from zero.
+    pub index: usize,
+    /// The error returned by the element type's `FromStr` implementation.
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for ParseError<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "failed to parse element {}: {}", self.index, self.error)
+    }
+}
+
+impl<E: Error + 'static> Error for ParseError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/**
+The error produced by `collect![validate ...]` when an element fails the predicate.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The position of the offending element, counting from zero.
+    pub index: usize,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "element {} failed validation", self.index)
+    }
+}
+
+impl Error for ValidationError {}
+
+/**
+The error produced by `collect![try_check ...]` when the finished collection fails the check.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CheckError;
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "collection failed its check")
+    }
+}
+
+impl Error for CheckError {}
@@ -41,169 +41,3700 @@ let f: HashMap<_, u8> = collect![as HashMap<i32, _>: 42 => 0, -11 => 2];
 # }
 ```
 
+## Spreads
+
+An element of the form `..EXPR` splices the contents of `EXPR` (which may be any `IntoIterator`) into the collection at that position.  Spreads can be freely mixed with regular sequence elements or map entries.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let evens = vec![2, 4, 6];
+let v: Vec<i32> = collect![0, ..evens, 8, ..10..12];
+assert_eq!(v, [0, 2, 4, 6, 8, 10, 11]);
+
+let defaults = vec![("a", 0), ("b", 0)];
+let m = collect![as HashMap<_, _>: ..defaults, "a" => 5];
+assert_eq!(m["a"], 5);
+# }
+```
+
+`collect![from SRC]` (or `collect![from SRC as TYPE]`) is shorthand for a collection containing a single spread of `SRC`.  This is handy for converting between collection types; for example, building a `HashMap` from a `Vec` of pairs allocates the map exactly once, since the `Vec`'s iterator knows its exact length.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let pairs = vec![(1, "one"), (2, "two")];
+let m = collect![from pairs as HashMap<_, _>];
+assert_eq!(m[&2], "two");
+# }
+```
+
+Since later entries replace earlier ones, spreading an inline `collect!` is a convenient way to start a map from a set of defaults, then override some of them:
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let m: HashMap<_, _> = collect![..collect![as HashMap<_, _>: "a" => 0, "b" => 0], "a" => 5];
+assert_eq!((m["a"], m["b"]), (5, 0));
+# }
+```
+
+Every element and spread is combined into a single iterator, so the first (and only) call to `Extend` sees the sum of their size hints.  This applies equally to sequences and maps: spreading an `ExactSizeIterator` of pairs into a `HashMap` reserves space for all of them up front.  Elements are still evaluated exactly once, from left to right.
+
+## Modifiers
+
+Modifiers appear before the `as` clause, and change how the collection is constructed.
+
+### `into`
+
+`collect![into TARGET; ...]` adds the elements to an existing collection, rather than creating a new one.  `TARGET` can be any place expression whose type implements `Extend`, including a `CollectBuilder`, which wraps a collection chosen at runtime.  All of the elements are passed to a single call to `Extend::extend`, through an iterator which reports its exact length, so the collection can reserve space for all of them at once.  Spreads aren't supported.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let mut v = vec![1];
+collect![into v; 2, 3];
+assert_eq!(v, [1, 2, 3]);
+# }
+```
+
+### `cloned`
+
+`collect![cloned as TYPE: ...]` calls `Iterator::cloned` on every spread source, allowing a collection of owned values to be built from borrowed ones.  This requires the element type to be `Clone`, and has no effect on regular (non-spread) elements.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let xs: &[i32] = &[1, 2, 3];
+let v = collect![cloned as Vec<i32>: 0, ..xs];
+assert_eq!(v, [0, 1, 2, 3]);
+# }
+```
+
+This is also how to spread a borrowed slice of key-value tuples into a map: `collect![cloned as MAP: ..pairs]` clones each tuple, which requires both the key and value types to be `Clone`.  As the slice knows its length, the map reserves space for all of its entries.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let pairs: &[(i32, &str)] = &[(1, "a"), (2, "b")];
+let m = collect![cloned as HashMap<_, _>: ..pairs];
+assert_eq!(m[&2], "b");
+# }
+```
+
+### `limit`
+
+`collect![limit N as TYPE: ...]` calls `Iterator::take(N)` on every spread source, so that each one contributes at most `N` items.  This guards against accidentally spreading an infinite iterator, such as `std::iter::repeat`, which would otherwise never finish.  Since the limited sources report sizes no larger than `N`, the collection still reserves space for everything up front.  `N` is evaluated once for each spread, and has no effect on regular elements.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![limit 3 as Vec<_>: 0, ..std::iter::repeat(1)];
+assert_eq!(v, [0, 1, 1, 1]);
+# }
+```
+
+### `sorted`, `sorted_by`, `sort_by_key`
+
+`collect![sorted as TYPE: ...]` builds the collection as normal, then sorts it in place with `sort`, which requires the element type to be `Ord`.  `collect![sorted_by (F) as TYPE: ...]` instead sorts with `sort_by`, using `F` as the comparator, and `collect![sort_by_key (F) as TYPE: ...]` sorts with `sort_by_key`, comparing the keys which `F` returns for each element.  All of these sorts are stable, so elements which compare equal keep the order they were written in, and are applicable to `Vec` and anything else which dereferences to a slice.
+
+Note that the sort is an additional `O(n log n)` pass over the collection, performed after construction, so it only starts once every element has been added.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![sorted as Vec<_>: 3, 1, 2];
+assert_eq!(v, [1, 2, 3]);
+
+let v = collect![sorted_by (|a: &i32, b: &i32| b.cmp(a)) as Vec<_>: 3, 1, 2];
+assert_eq!(v, [3, 2, 1]);
+
+let v = collect![sort_by_key (|s: &&str| s.len()) as Vec<_>: "ccc", "a", "bb"];
+assert_eq!(v, ["a", "bb", "ccc"]);
+# }
+```
+
+### `dedup_adjacent`
+
+`collect![dedup_adjacent: ...]` builds a `Vec`, then removes consecutive repeated elements with `dedup`, which requires the element type to be `PartialEq`.  Only *adjacent* duplicates are removed; to remove all of them, combine it with `sorted`, or collect into a set instead.  Use `collect![dedup_adjacent as TYPE: ...]` to select a different collection type, which must have an inherent `dedup` method.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![dedup_adjacent: 1, 1, 2, 3, 3, 1], [1, 2, 3, 1]);
+assert_eq!(collect![dedup_adjacent sorted as Vec<_>: 1, 1, 2, 3, 3, 1], [1, 2, 3]);
+# }
+```
+
+### `dedup_by_key`
+
+`collect![dedup_by_key (F): ...]` builds a `Vec`, then removes every element whose key, as returned by `F`, has already been seen, keeping only the first element for each key.  Unlike `dedup_adjacent`, the duplicates needn't be next to each other, and the remaining elements stay in their original order.  `F` is called with a reference to each element, and the key type must be `Eq + Hash`; the keys are tracked in a temporary `HashSet`.  Use `collect![dedup_by_key (F) as Vec<_>: ...]` to combine it with other modifiers.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![dedup_by_key (|s: &&str| s.len()): "a", "bb", "c", "dd", "eee"];
+assert_eq!(v, ["a", "bb", "eee"]);
+# }
+```
+
+### `rotate`
+
+`collect![rotate N as TYPE: ...]` builds the collection as normal, then rotates it left by `N` places with `rotate_left`, so that the element at index `N` ends up first.  This is applicable to `VecDeque`, and to `Vec` and anything else which dereferences to a slice.  `N` is taken modulo the length of the collection, so it may be larger than the number of elements.  The rotation is an additional `O(n)` pass, performed after construction; `N` is evaluated after all of the elements.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::VecDeque;
+# fn main() {
+let d = collect![rotate 2 as VecDeque<_>: 1, 2, 3, 4, 5];
+assert_eq!(d, [3, 4, 5, 1, 2]);
+# }
+```
+
+### `pad_to`
+
+`collect![pad_to LEN with FILL as TYPE: ...]` builds the collection as normal, then appends clones of `FILL` until its `len` reaches `LEN`.  This is intended for sequences such as `Vec` and `VecDeque`.  If there are already `LEN` or more elements, nothing is added, and nothing is removed.  When used directly before `as`, space for all `LEN` elements is reserved up front.  `LEN` and `FILL` are evaluated before any of the elements.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![pad_to 5 with 0 as Vec<_>: 1, 2, 3];
+assert_eq!(v, [1, 2, 3, 0, 0]);
+# }
+```
+
+### `terminate_with`
+
+`collect![terminate_with S as TYPE: ...]` builds the collection as normal, then appends the sentinel `S` after the last element, in the style of a null-terminated array for C APIs.  `collect![terminate_with S: ...]` builds a `Vec`.  When used directly before `as`, space for the sentinel is reserved along with the elements, so the collection still only allocates once.  `S` is evaluated before any of the elements.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![terminate_with 0: 1, 2, 3], [1, 2, 3, 0]);
+# }
+```
+
+### `check`, `try_check`
+
+`collect![check (F) as TYPE: ...]` builds the collection as normal, then calls `F` with a reference to it, and panics if `F` returns `false`.  This is for enforcing invariants on the finished collection, such as a maximum size.  `collect![try_check (F) as TYPE: ...]` returns a `Result<TYPE, CheckError>` instead of panicking.  Either form without `as` builds a `Vec`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![check (|c: &Vec<_>| c.len() <= 3) as Vec<_>: 1, 2, 3];
+assert_eq!(v, [1, 2, 3]);
+assert!(collect![try_check (|c: &Vec<_>| c.len() <= 3): 1, 2, 3, 4].is_err());
+# }
+```
+
+### `rev`
+
+`collect![rev as TYPE: ...]` builds the collection as normal, then reverses it.  `Vec` and `VecDeque` are reversed in place, without allocating.  A `String` is reversed by `char`, not by byte, so that the result is still valid UTF-8; note that this will split up grapheme clusters, such as a letter followed by a combining accent.  Reversing a `String` builds a new one of the same length.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![rev as Vec<_>: 1, 2, 3], [3, 2, 1]);
+assert_eq!(collect![rev as String: "ab", "ç"], "çba");
+# }
+```
+
+### `shrink`
+
+`collect![shrink as TYPE: ...]` builds the collection as normal, then calls `shrink_to_fit` on it, so that it holds no more memory than it needs.  This is mostly useful together with modifiers which deliberately over-allocate, such as `pow2` or `headroom`, for collections which won't grow any further.  Shrinking may reallocate the collection, in which case the elements are copied into a new, smaller allocation.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![shrink pow2 as Vec<_>: 1, 2, 3];
+assert_eq!(v.capacity(), 3);
+# }
+```
+
+### `with_hash`
+
+`collect![with_hash as TYPE: ...]` builds the collection as normal, then returns it in a tuple along with a `u64` hash of its contents, for use as a checksum.  The hash is computed during insertion, rather than in a second pass: each element and spread item is fed to a `DefaultHasher::new()` as it is passed to `Extend`, in order.  As a result, it's the element type which must implement `Hash`, not the collection, and every element counts towards the hash, even a duplicate that a set or map drops.  `DefaultHasher`'s algorithm is unspecified, so the hash is only stable within a single build of a program; don't persist it.
+
+Because it changes the result type, `with_hash` must come directly before `as`, and can only be combined with `timed`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let (v, hash) = collect![with_hash as Vec<u8>: 1, 2, 3];
+assert_eq!(v, [1, 2, 3]);
+assert_eq!(collect![with_hash as Vec<u8>: 1, 2, 3].1, hash);
+# }
+```
+
+### `timed`
+
+`collect![timed as TYPE: ...]` builds the collection as normal, then returns it in a tuple along with the `std::time::Duration` it took to build, as measured by `Instant`.  This is meant for quick comparisons in your own benchmarks, such as between a spread and a naive `collect`; the measurement includes evaluating the elements and spread sources themselves.  Like `with_hash`, it changes the result type, so it must come before any other modifiers, including `with_hash`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let (v, elapsed) = collect![timed as Vec<_>: ..0..1000];
+assert_eq!(v.len(), 1000);
+println!("built in {:?}", elapsed);
+# }
+```
+
+### `outline`
+
+Normally, `collect!` expands to a single call to `Extend::extend` with an array of the elements, all of which is inlined into the calling function.  For very large tables, this can bloat the caller considerably.  `collect![outline as TYPE: ...]` instead passes that array to a single, non-inlined function which does the insertion; this function is shared by every use with the same collection type and element count.
+
+The tradeoff is that the insertion loop can no longer be optimised together with the surrounding code.  This form does not support spreads.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![outline as Vec<_>: 1, 2, 3];
+assert_eq!(v, [1, 2, 3]);
+# }
+```
+
+### `prealloc`
+
+`collect!` normally evaluates every element into a temporary array, then passes that array to a single `Extend` call, which reserves space for all of them at once.  `collect![prealloc as TYPE: ...]` instead creates the collection with `with_capacity` *before* evaluating any of the elements, then adds each one as it is computed.  This keeps the allocation separate from expensive element expressions, which can make profiles easier to read.  Spreads aren't supported.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+# fn expensive(x: i32) -> i32 { x }
+let v = collect![prealloc as Vec<_>: expensive(1), expensive(2)];
+assert_eq!(v, [1, 2]);
+# }
+```
+
+### `in`
+
+With the `nightly` feature enabled, `collect![in ALLOC as TYPE: ...]` creates the collection with `with_capacity_in`, using `ALLOC` as its allocator, then adds each element in turn.  This requires a nightly compiler, and the unstable `allocator_api` feature in your own crate to name the collection type.  `Vec` and `VecDeque` are supported, and spreads aren't.
+
+```ignore
+#![feature(allocator_api)]
+# #[macro_use] extern crate collect_mac;
+use std::alloc::Global;
+# fn main() {
+let v = collect![in Global as Vec<_, Global>: 1, 2, 3];
+assert_eq!(v, [1, 2, 3]);
+# }
+```
+
+### `via_from_iter`
+
+Some collections implement `FromIterator`, but not `Default` or `Extend`; immutable collections are a common example.  `collect![via_from_iter as TYPE: ...]` builds these by passing all of the elements to `FromIterator::from_iter` in one go, through an iterator which reports its exact length.  As with `outline`, the elements are first moved into a temporary array, and spreads aren't supported.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::BTreeMap;
+# fn main() {
+let m = collect![via_from_iter as BTreeMap<_, _>: 1 => "a", 2 => "b"];
+assert_eq!(m[&2], "b");
+# }
+```
+
+### `ring`
+
+`collect![ring N as TYPE: ...]` creates the collection using `with_capacity` (so `TYPE` must have such a method), asking for at least `N` elements worth of space.  This is intended for `VecDeque`s which are about to be used as ring buffers, where a predictable capacity (such as a power of two) is useful.  If there are more than `N` elements, the capacity will be enough for all of them instead.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::VecDeque;
+# fn main() {
+let d = collect![ring 16 as VecDeque<_>: 1, 2, 3];
+assert!(d.capacity() >= 16);
+# }
+```
+
+### `reserve`
+
+`collect![reserve N as TYPE: ...]` creates the collection using `with_capacity`, asking for enough space for all of the elements *plus* `N` more.  This is useful when more elements are going to be pushed onto the collection immediately after it is built.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![reserve(32) as Vec<_>: 1, 2, 3];
+assert_eq!(v.len(), 3);
+assert!(v.capacity() >= 35);
+# }
+```
+
+### `pow2`
+
+`collect![pow2 as TYPE: ...]` creates the collection using `with_capacity`, asking for space for the number of elements rounded up to the next power of two; with no elements, nothing is allocated.  This suits allocators which prefer power-of-two sizes, and collections which are likely to grow to one.  Like `ring`, `reserve`, `headroom` and `cap_from`, this decides the initial capacity, so at most one of them should be used.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![pow2 as Vec<_>: 1, 2, 3];
+assert!(v.capacity() >= 4);
+# }
+```
+
+### `headroom`
+
+`collect![headroom as TYPE: ...]` creates the collection using `with_capacity`, asking for space for `n * 8 / 7 + 1` elements, where `n` is the number of elements.  This leaves room for a hash table's maximum load factor of 7/8, for tables which count their capacity in buckets rather than elements.
+
+The standard `HashMap` and `HashSet` already account for their load factor in `with_capacity` and `reserve`, so they never need to grow while `collect!` is inserting the elements, with or without this modifier.  Like `ring`, `reserve`, `pow2` and `cap_from`, this decides the initial capacity, so at most one of them should be used.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashSet;
+# fn main() {
+let s = collect![headroom as HashSet<_>: 1, 2, 3];
+assert!(s.capacity() >= 4);
+# }
+```
+
+### `cap_from`
+
+`collect![cap_from(N) as TYPE: ...]` creates the collection using `with_capacity(N)`, ignoring the size hints of the elements and spreads entirely.  This is for when you know how many elements there will be, but a spread source doesn't say so, such as a `filter` or a hand-written iterator.  If `N` is too small, the collection will still grow as needed.  Like `ring`, `reserve`, `pow2` and `headroom`, this decides the initial capacity, so at most one of them should be used.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![cap_from(5) as Vec<_>: ..(0..10).filter(|i| i % 2 == 0)];
+assert_eq!(v.capacity(), 5);
+# }
+```
+
+### `expected`
+
+`collect![as TYPE: expected(N); ...]` creates the collection using `with_capacity(N)`, where `N` is the number of *distinct* elements you expect, and hides the size hints of any spreads from it.  This is for sets and maps which are fed many duplicates: normally, a spread of a thousand elements would reserve space for a thousand entries, even if they boil down to five.  The capacity is only a hint, so if there turn out to be more distinct elements, the collection grows as usual.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashSet;
+# fn main() {
+let s = collect![as HashSet<_>: expected(3); ..(0..1000).map(|i| i % 3)];
+assert_eq!(s.len(), 3);
+assert!(s.capacity() < 1000);
+# }
+```
+
+### `assert_single_alloc`
+
+`collect![assert_single_alloc as TYPE: ...]` guards against a collection allocating more than once while it is built, which would otherwise go unnoticed.  The collection is built as normal, with a single `extend` call; in debug builds, its capacity is then compared with that of an empty collection which has reserved space for every element, and it panics if the two differ, since that means the collection grew more than once.  For example, building a `String` from long enough `&str` pieces will fail, since it reserves space as each piece arrives, rather than up front.  In release builds, the check is skipped.  `TYPE` must have `capacity` and `reserve` methods.  Both sequences and maps are supported, but spreads aren't.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![assert_single_alloc as Vec<_>: 1, 2, 3];
+assert_eq!(v, [1, 2, 3]);
+# }
+```
+
+Modifiers which act on the finished collection, such as `sorted`, can be combined with one another and with other modifiers; they are applied from right to left.  They must all appear before any modifiers which affect how the collection is built, such as `cloned`, `limit`, `ring`, `reserve`, `pow2`, `headroom` or `cap_from`.
+
+## Specialised forms
+
+In addition to the plain sequence and map forms, `collect!` supports a number of specialised forms, each introduced by a leading keyword.
+
+Where a form (or modifier) takes an argument which is followed by more syntax (such as `as TYPE`), that argument must be a single token tree.  Simple literals and identifiers can be written as-is; anything more complex, such as a closure, must be wrapped in parentheses.
+
+### `range_map`
+
+`collect![range_map KEYS => F]` builds a map from each key produced by `KEYS` (which may be any `IntoIterator`, such as a range) to the value computed by calling `F` with a reference to that key.  By default, the result is a `BTreeMap`; use `collect![range_map as MAP: KEYS => F]` to select a different map type.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let squares = collect![range_map 0..5 => |k| k*k];
+assert_eq!(squares.get(&3), Some(&9));
+
+let cubes = collect![range_map as HashMap<_, _>: 0..5 => |k| k*k*k];
+assert_eq!(cubes.get(&3), Some(&27));
+# }
+```
+
+The contents of the resulting map are deterministic, but note that the iteration order of a `HashMap` is unspecified.
+
+`collect![map_values (F) over KEYS]` does the same thing, but builds a `HashMap` by default; use `collect![map_values (F) over KEYS as MAP]` to select a different map type.  Either way, the map reserves space for as many entries as `KEYS` reports.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let squares = collect![map_values (|k| k*k) over 1..=3];
+assert_eq!(squares[&3], 9);
+# }
+```
+
+### `table`
+
+`collect![table (RANGE): F]` builds a `HashMap` by calling `F` with each index in `RANGE` (or any other `IntoIterator`), which must return a `(key, value)` tuple; use `collect![table (RANGE) as MAP: F]` to select a different map type.  The map reserves space for as many entries as `RANGE` reports.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let squares = collect![table (0..3): |i| (i, i*i)];
+assert_eq!(squares[&2], 4);
+# }
+```
+
+### `gen`
+
+`collect![gen N: F]` builds a `Vec` of `N` values by calling `F` that many times, such as to draw random samples; use `collect![gen N as TYPE: F]` to select a different collection type.  Unlike `table`, `F` takes no arguments.  The collection reserves space for all `N` values up front.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let mut next = 0;
+let v = collect![gen 3: || { next += 2; next }];
+assert_eq!(v, [2, 4, 6]);
+# }
+```
+
+### `index_by`
+
+`collect![index_by (F) from ITER]` builds a `HashMap` from each item in `ITER` to itself, keyed by the result of calling `F` with a reference to that item.  Because the closure only borrows the item, the key it returns must be owned (or borrowed from somewhere else).  If two items produce the same key, the last one wins.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+struct Person { id: u32, name: &'static str }
+let people = vec![Person { id: 7, name: "Alice" }, Person { id: 3, name: "Bob" }];
+let by_id = collect![index_by (|p: &Person| p.id) from people];
+assert_eq!(by_id[&3].name, "Bob");
+# }
+```
+
+### `group_count`
+
+`collect![group_count (F) from ITER]` builds a `HashMap<K, usize>` of how many items in `ITER` fall into each group, where the group of an item is the key returned by calling `F` with a reference to it; use `collect![group_count (F) from ITER as MAP]` to select a different map type with an `entry` API.  As with `index_by`, the key must be owned (or borrowed from somewhere else).  Groups with no items don't appear in the map at all.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let words = ["apple", "avocado", "banana"];
+let by_letter = collect![group_count (|w: &&str| w.as_bytes()[0]) from words];
+assert_eq!((by_letter[&b'a'], by_letter[&b'b']), (2, 1));
+# }
+```
+
+### `classify`
+
+`collect![classify (F) from ITER]` builds a `HashMap<K, Vec<T>>` by sorting the items of `ITER` into buckets, where the bucket of an item is the key returned by calling `F` with a reference to it.  This is like `group_count`, except that it keeps the items themselves, in their original order within each bucket.  Use `collect![classify (F) from ITER as MAP]` to select a different map type with an `entry` API, or a different bucket type, which must implement `Default` and `Extend`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let parity = collect![classify (|n: &i32| n % 2 == 0) from 1..6];
+assert_eq!(parity[&true], [2, 4]);
+assert_eq!(parity[&false], [1, 3, 5]);
+# }
+```
+
+### `counted`
+
+`collect![counted as MAP: K => V, ...]` builds a map using its `insert` method, and returns it along with the number of entries which replaced an existing one.  This is useful for detecting accidental duplicate keys without treating them as an error.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let (m, overwrites) = collect![counted as HashMap<_, _>: "a" => 1, "b" => 2, "a" => 3];
+assert_eq!(m["a"], 3);
+assert_eq!(overwrites, 1);
+# }
+```
+
+### `ordered_counts`
+
+`collect![ordered_counts: A, B, ...]` builds a `BTreeMap<T, usize>` from each distinct element to the number of times it appears, so that iterating over the tallies visits the elements in sorted order.  Use `collect![ordered_counts as MAP: ...]` to select a different map type with an `entry` API, such as a `HashMap`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let counts = collect![ordered_counts: 'b', 'a', 'b', 'c'];
+assert_eq!(counts.into_iter().collect::<Vec<_>>(), [('a', 1), ('b', 2), ('c', 1)]);
+# }
+```
+
+### `try_values`
+
+`collect![try_values: K => V, ...]` builds a `HashMap` where each value is a `Result`, returning `Result<HashMap<K, T>, E>`; use `collect![try_values as MAP: ...]` to select a different map type.  At the first `Err`, construction stops and the error is returned; none of the later entries are evaluated.  Keys are plain values, and aren't expected to fail.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let ok = collect![try_values: 1 => "10".parse::<i32>(), 2 => "20".parse()];
+assert_eq!(ok.unwrap()[&2], 20);
+
+let err = collect![try_values: 1 => "10".parse::<i32>(), 2 => "x".parse()];
+assert!(err.is_err());
+# }
+```
+
+### `try_gen`
+
+`collect![try_gen (RANGE): F]` calls `F` with each index in `RANGE`, where `F` returns a `Result`, and builds a `Result<Vec<T>, E>`; use `collect![try_gen (RANGE) as TYPE: F]` to select a different collection type.  At the first `Err`, generation stops and the error is returned; `F` isn't called for any of the later indices.  `RANGE` can be any iterable, but must be parenthesised unless it is a single token.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let ok = collect![try_gen (0..3): |i| "0123".get(i..i + 1).ok_or(i)];
+assert_eq!(ok, Ok(vec!["0", "1", "2"]));
+
+let err = collect![try_gen (0..9): |i| "0123".get(i..i + 1).ok_or(i)];
+assert_eq!(err, Err(4));
+# }
+```
+
+### `sum_map`
+
+`collect![sum_map: K => V, ...]` builds a `HashMap` in which the values of duplicate keys are added together with `+=`, starting from `Default::default()`; use `collect![sum_map as MAP: ...]` to select a different map type with an `entry` API.  The value type must implement `AddAssign` and `Default`, which covers all of the primitive numeric types.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![sum_map: "a" => 1, "a" => 2, "b" => 3];
+assert_eq!((m["a"], m["b"]), (3, 3));
+# }
+```
+
+### `group_set`
+
+`collect![group_set: K => V, ...]` builds a `HashMap<K, HashSet<V>>`, adding each value to the set for its key, so that repeated key-value pairs collapse into one; use `collect![group_set as MAP: ...]` to select different map or set types.  The map needs an `entry` API, and the set type must implement `Default` and have an `insert` method.  The value type must be `Eq + Hash` for a `HashSet`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![group_set: 1 => "a", 1 => "a", 2 => "b", 1 => "c"];
+assert_eq!((m[&1].len(), m[&2].len()), (2, 1));
+# }
+```
+
+### `no_overwrite`
+
+`collect![no_overwrite: K => V, ...]` builds a `HashMap`, and panics with the offending key if any key is given more than once, rather than silently keeping the last value; use `collect![no_overwrite as MAP: ...]` to select a `HashMap` with a different hasher.  The key type must be `Debug`.  With the `nightly` feature enabled, each entry is inserted with `HashMap::try_insert`; otherwise, the `entry` API is used to the same effect.  To get a `Result` instead of a panic, see [`try_collect_map!`](macro.try_collect_map.html).
+
+```should_panic
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![no_overwrite: "a" => 1, "b" => 2, "a" => 3];
+# }
+```
+
+### `heapify`
+
+`collect![heapify from SRC]` and `collect![heapify: ...]` build a `BinaryHeap` by first collecting the elements into a `Vec`, then converting it with `BinaryHeap::from`.  Pushing elements into a heap one at a time (which is what `Extend` does) is `O(n log n)`, whereas heapifying a `Vec` is `O(n)`.  When `SRC` is already a `Vec`, it is reused without reallocating.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let mut h = collect![heapify: 3, 1, 4, 1, 5];
+assert_eq!(h.pop(), Some(5));
+
+let mut h = collect![heapify from vec![2, 7, 1]];
+assert_eq!(h.pop(), Some(7));
+# }
+```
+
+### `chars`
+
+`collect![chars from S]` builds a `Vec<char>` from the characters of `S`, which may be anything that implements `AsRef<str>`.  Since a string can't contain more characters than it does bytes, the `Vec` is allocated once using the byte length of the string, which may over-allocate for non-ASCII text.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![chars from "hello"], ['h', 'e', 'l', 'l', 'o']);
+# }
+```
+
+### `upper`, `lower`
+
+`collect![upper: S, ...]` builds a `String` from the uppercased pieces `S`, each of which may be a `&str`, `String` or `char`; `collect![lower: ...]` lowercases them instead.  As with any literal list, all of the pieces must have the same type.  The `String` reserves space for the combined byte length of the pieces before conversion, which is only an estimate: some characters change length, such as `'ß'`, which uppercases to `"SS"`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![upper: "hello", "world"], "HELLOWORLD");
+assert_eq!(collect![lower as String: 'A', 'Б'], "aб");
+# }
+```
+
+### `hex`, `try_hex`
+
+`collect![hex from S]` builds a `Vec<u8>` by decoding `S` (which may be anything that implements `AsRef<str>`) as pairs of hexadecimal digits, allocating exactly once.  It panics if `S` has an odd length or contains anything other than hexadecimal digits.  `collect![try_hex from S]` instead returns a `Result<Vec<u8>, HexError>`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![hex from "deadBEEF"], [0xde, 0xad, 0xbe, 0xef]);
+assert!(collect![try_hex from "abc"].is_err());
+# }
+```
+
+### `bits`, `try_bits`
+
+`collect![bits from S]` builds a `Vec<u8>` by decoding `S` (which may be anything that implements `AsRef<str>`) as a string of binary digits, most significant bit first, eight to a byte.  If the number of digits isn't a multiple of eight, the last byte is padded with zeros on the right.  It panics if `S` contains anything other than `0` and `1`; `collect![try_bits from S]` instead returns a `Result<Vec<u8>, BitsError>`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![bits from "10110001"], [0b1011_0001]);
+assert_eq!(collect![bits from "111100001111"], [0b1111_0000, 0b1111_0000]);
+assert!(collect![try_bits from "10_1"].is_err());
+# }
+```
+
+### `flatten`
+
+`collect![flatten: A, B, ...]` builds a `Vec` containing the elements of each of `A`, `B`, *etc.* in order; use `collect![flatten as TYPE: ...]` to select a different collection type.  This is equivalent to spreading each of the inner sequences, which means that if they are all array literals (or other exactly-sized iterables), the collection is allocated exactly once, even when the inner sequences differ in length.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![flatten: [1, 2], [3, 4], [5, 6]];
+assert_eq!(v, [1, 2, 3, 4, 5, 6]);
+# }
+```
+
+### `flatten_all`
+
+`collect![flatten_all: OUTER]` builds a `Vec` from the elements of every inner sequence in the single nested sequence `OUTER`, such as a `Vec<Vec<T>>`, consuming it; use `collect![flatten_all as TYPE: OUTER]` to select a different collection type, which must have a `with_capacity` method.  The inner lengths are added up first, so the result is allocated exactly once.  This means `OUTER` must have an `iter` method, and the inner sequences a `len` method, as `Vec`, `VecDeque` and arrays do.  Only one level of nesting is removed.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let nested = vec![vec![1, 2], vec![], vec![3]];
+assert_eq!(collect![flatten_all: nested], [1, 2, 3]);
+# }
+```
+
+### `btree_union`, `btree_intersection`
+
+`collect![btree_union: A, B, ...]` builds a `BTreeSet` containing every element which appears in any of the given sets, cloning the elements.  Since `BTreeSet` has no notion of capacity, this simply extends the result with each source in turn.  Any source with an `iter` method will do, although `BTreeSet`s are the natural choice.
+
+`collect![btree_intersection: A, B, ...]` builds a `BTreeSet` containing only those elements which appear in *every* given set, using `BTreeSet::intersection`.  All sources after the first must be `BTreeSet`s.
+
+Both forms borrow their sources, and require the element type to be `Ord + Clone`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::BTreeSet;
+# fn main() {
+let a: BTreeSet<_> = collect![1, 2, 3];
+let b: BTreeSet<_> = collect![2, 3, 4];
+assert_eq!(collect![btree_union: a, b], collect![as BTreeSet<_>: 1, 2, 3, 4]);
+assert_eq!(collect![btree_intersection: a, b], collect![as BTreeSet<_>: 2, 3]);
+# }
+```
+
+### `btree_merge`
+
+`collect![btree_merge: A, B, ...]` consumes several `BTreeMap`s and merges them into one, moving every later map into the first with `BTreeMap::append`.  This is considerably cheaper than inserting the entries one at a time.  When the same key appears in more than one map, the value from the *last* map containing it wins.  To keep the originals, pass clones.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::BTreeMap;
+# fn main() {
+let a: BTreeMap<_, _> = collect![1 => "a", 2 => "a"];
+let b: BTreeMap<_, _> = collect![2 => "b", 3 => "b"];
+assert_eq!(collect![btree_merge: a, b], collect![as BTreeMap<_, _>: 1 => "a", 2 => "b", 3 => "b"]);
+# }
+```
+
+### `parse`
+
+`collect![parse as TYPE: S, ...]` builds a collection by parsing each element (which may be anything that implements `AsRef<str>`, so long as all of the elements have the same type) with the `FromStr` implementation of the collection's element type.  The result is a `Result<TYPE, ParseError<E>>`, where `E` is the element type's parse error.  Parsing stops at the first failure, and the error records the position of the element which failed.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![parse as Vec<i32>: "1", "2", "3"], Ok(vec![1, 2, 3]));
+assert_eq!(collect![parse as Vec<i32>: "1", "x", "3"].unwrap_err().index, 1);
+# }
+```
+
+### `csv`, `csv_parse`
+
+`collect![csv from S]` splits the string slice `S` at every comma, and builds a `Vec<&str>` of the fields with any surrounding whitespace trimmed; use `collect![csv as TYPE: S]` to select a different collection type, which must have a `with_capacity` method.  The commas are counted first, so the collection is allocated exactly once.  There is no support for quoting or escaping, so this is only suited to simple, single-line data; note that an empty string is a single, empty field.
+
+`collect![csv_parse as TYPE: S]` also parses each field with the `FromStr` implementation of the collection's element type, just like `parse`, and returns a `Result<TYPE, ParseError<E>>`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![csv from "a, b,c "], ["a", "b", "c"]);
+assert_eq!(collect![csv_parse as Vec<i32>: "1, 2, 3"], Ok(vec![1, 2, 3]));
+assert_eq!(collect![csv_parse as Vec<i32>: "1, x"].unwrap_err().index, 1);
+# }
+```
+
+### `invert`
+
+`collect![invert from SRC]` consumes a map (or any other iterable of key-value pairs), and builds a `HashMap` from each value to its key; use `collect![invert from SRC as MAP]` to select a different map type.  If several keys share the same value, the one which comes last in `SRC`'s iteration order wins; for a `HashMap`, that order is unspecified.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::{BTreeMap, HashMap};
+# fn main() {
+let m: HashMap<_, _> = collect![1 => "a", 2 => "b"];
+let inv = collect![invert from m as BTreeMap<_, _>];
+assert_eq!(inv, collect!["a" => 1, "b" => 2]);
+# }
+```
+
+### `key_set`, `value_set`
+
+`collect![key_set from MAP]` builds a `HashSet` of clones of the keys of `MAP`, which is only borrowed; `collect![value_set from MAP]` does the same for its values, so repeated values appear only once.  Use `collect![key_set from MAP as SET]` or `collect![value_set from MAP as SET]` to select a different set type.  Either way, the set reserves space for `MAP.len()` elements.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let m: HashMap<_, _> = collect!["a" => 1, "b" => 2, "c" => 1];
+assert_eq!(collect![key_set from m], collect!["a", "b", "c"]);
+assert_eq!(collect![value_set from m], collect![1, 2]);
+# }
+```
+
+### `pages`
+
+`collect![pages N from SRC]` splits the items of `SRC` into consecutive chunks of `N`, and builds a `HashMap<usize, Vec<T>>` from each page number (counting from zero) to its chunk; use `collect![pages N from SRC as MAP]` to select a different map type.  Every page is full except possibly the last, and since page numbers are unique, no page can overwrite another.  The map reserves space for as many pages as `SRC`'s size hint implies.  `N` must not be zero.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let p = collect![pages 2 from 0..5];
+assert_eq!((&p[&0], &p[&2]), (&vec![0, 1], &vec![4]));
+# }
+```
+
+### `split_on`
+
+`collect![split_on D from SRC]` splits the slice `SRC` at every element equal to the delimiter `D`, and builds a `Vec` of the segments in between, such as a `Vec<&[u8]>` for a byte slice; use `collect![split_on D from SRC as TYPE]` to select a different collection type, which must have a `with_capacity` method.  The delimiters themselves are dropped.  As with `slice::split`, a leading or trailing delimiter, or two consecutive delimiters, produce an empty segment, so `n` delimiters always give `n + 1` segments.  The delimiters are counted first, so that the collection is allocated exactly once.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let data: &[u8] = b"GET /\r\nHost: x\r\n";
+let lines = collect![split_on b'\n' from data];
+assert_eq!(lines, [&b"GET /\r"[..], b"Host: x\r", b""]);
+# }
+```
+
+### `windows`
+
+`collect![windows from SRC]` builds a `Vec` of each pair of adjacent items from `SRC`, so that `n` items produce `n - 1` overlapping pairs (or none at all, for fewer than two items); use `collect![windows from SRC as TYPE]` to select a different collection type.  Every item other than the first and last appears in two pairs, so the item type must be `Clone`.  When `SRC` reports an exact size, the result is allocated exactly once.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![windows from 0..4], vec![(0, 1), (1, 2), (2, 3)]);
+# }
+```
+
+### `dense`
+
+`collect![dense LEN default D: I => V, ...]` builds a `Vec` of length `LEN` filled with clones of `D`, then sets the element at each index `I` to `V`, which is handy for turning a sparse description into a dense one.  It panics if any index is out of bounds.  If an index is given more than once, the last value wins.  `LEN` and `D` are evaluated first, then each index and value in turn.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![dense 5 default 0: 1 => 10, 3 => 30];
+assert_eq!(v, [0, 10, 0, 30, 0]);
+# }
+```
+
+### `interleave`
+
+`collect![interleave: (A1, A2, ...), (B1, B2, ...)]` builds a `Vec` by alternating between the elements of two parenthesised groups, giving `[A1, B1, A2, B2, ...]`; use `collect![interleave as TYPE: ...]` to select a different collection type.  The collection is allocated once, with space for both groups.  Elements are evaluated in the order they appear in the *result*, not the order they are written.
+
+Both groups must have the same number of elements; unequal groups are a compile-time error, rather than being truncated or padded.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![interleave: (1, 2, 3), (10, 20, 30)], [1, 10, 2, 20, 3, 30]);
+# }
+```
+
+```compile_fail
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v: Vec<i32> = collect![interleave: (1, 2, 3), (10, 20)];
+# }
+```
+
+### `with_index`
+
+`collect![with_index: A, B, ...]` builds a `Vec` of `(index, element)` pairs, numbering the elements from zero in the order they are written; use `collect![with_index as TYPE: ...]` to select a different collection type.  Note that the index comes first, so collecting into a map keys each element by its position.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![with_index: "a", "b", "c"], [(0, "a"), (1, "b"), (2, "c")]);
+# }
+```
+
+### `rank_map`
+
+`collect![rank_map: A, B, ...]` builds a `HashMap` from each element to its rank, counting from *one* in the order they are written, which suits leaderboards and other human-facing orderings; use `collect![rank_map as MAP: ...]` to select a different map type.  Unlike `with_index`, the element is the key.  If an element appears more than once, the last rank wins.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let ranks = collect![rank_map: "gold", "silver", "bronze"];
+assert_eq!((ranks["gold"], ranks["bronze"]), (1, 3));
+# }
+```
+
+### `scan`
+
+`collect![scan INIT, (F) over SRC]` builds a `Vec` from the values produced by `Iterator::scan`: `F` is called with a mutable reference to the state (which starts as `INIT`) and each item from `SRC` in turn, and the output stops at the first `None` it returns.  Use `collect![scan INIT, (F) over SRC as TYPE]` to select a different collection type, which must have a `with_capacity` method.
+
+Because the scan can stop early, the collection only reserves space for the scan's *lower* bound, and grows as the scan produces values.  This means `SRC` can be arbitrarily large (or infinite, as with `1..`) so long as the scan stops in good time.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let sums = collect![scan 0, (|acc: &mut i32, x| { *acc += x; Some(*acc) }) over 1..=4];
+assert_eq!(sums, [1, 3, 6, 10]);
+# }
+```
+
+### `seq`
+
+`collect![seq from INIT, F]` builds a `Vec` from the sequence produced by `std::iter::successors`: it starts with `INIT`, then calls `F` with a reference to the latest item to get the next one, and stops at the first `None`.  Use `collect![seq from INIT, (F) as TYPE]` to select a different collection type.  The length of the sequence can't be known in advance, so the collection grows as needed.  `INIT` is always included, even if `F` returns `None` straight away.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![seq from 1, |&x| if x < 64 { Some(x * 2) } else { None }];
+assert_eq!(v, [1, 2, 4, 8, 16, 32, 64]);
+# }
+```
+
+### `intersperse`
+
+`collect![intersperse SEP: A, B, ...]` builds a `Vec` with a clone of `SEP` between each pair of adjacent elements; use `collect![intersperse SEP as TYPE: ...]` to select a different collection type.  For `n` elements, the result contains `2n - 1` items, with no leading or trailing separator, and is allocated exactly once.  `SEP` is evaluated once, before any of the elements, and must be `Clone`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![intersperse 0: 1, 2, 3];
+assert_eq!(v, [1, 0, 2, 0, 3]);
+# }
+```
+
+### `weave`
+
+`collect![weave SEPS: A, B, ...]` builds a `Vec` like `intersperse`, except that the separators are taken one at a time from the iterable `SEPS`, so each gap can get a different one; use `collect![weave SEPS as TYPE: ...]` to select a different collection type, which must have a `with_capacity` method.  If `SEPS` runs out, the remaining elements are added with no separators between them, and any separators left over are ignored.  Each separator is taken just before the element which follows it is evaluated.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![weave (vec![0, -1]): 1, 2, 3, 4];
+assert_eq!(v, [1, 0, 2, -1, 3, 4]);
+# }
+```
+
+### `fmt`, `fmt_join`, `display_join`
+
+`collect![fmt: A, B, ...]` builds a `String` by formatting each element with its `Display` implementation, writing them one after another.  `collect![fmt_join SEP: A, B, ...]` does the same, but also writes `SEP` (which only needs to implement `Display`) between each pair of elements.  Use `collect![fmt as TYPE: ...]` or `collect![fmt_join SEP as TYPE: ...]` to write into some other type which implements `std::fmt::Write` and has a `with_capacity` method.
+
+Since the formatted length of each element isn't known in advance, the string is created with room for one byte per element, and will likely need to grow.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![fmt: 1, '+', 2.5], "1+2.5");
+assert_eq!(collect![fmt_join ", ": 1, 2, 3], "1, 2, 3");
+# }
+```
+
+`collect![display_join SEP from ITER]` is like `fmt_join`, but formats the items of an iterator (or anything else which implements `IntoIterator`), rather than a list of elements; use `collect![display_join SEP from ITER as TYPE]` to select a different type.  Since the lengths of the formatted items aren't known in advance, the string reserves space for a best guess based on the iterator's size hint, and may still need to grow.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![display_join ", " from 1..4], "1, 2, 3");
+# }
+```
+
+### `keys`
+
+`collect![keys as MAP: K, ...]` builds a map where each listed key is paired with `Default::default()` for its value.  The value type must implement `Default`, and must be inferable from the type annotation.  `collect![keys: K, ...]` leaves the map type to be inferred from context.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let m = collect![keys as HashMap<&str, Vec<i32>>: "a", "b"];
+assert_eq!(m["a"], []);
+# }
+```
+
+### `arrays`
+
+`collect![arrays as MAP: [K, V], ...]` builds a map from two-element array literals, treating the first element of each as the key and the second as the value.  The arrays are taken apart by the macro, so the key and value may have different types.  Anything other than a two-element array literal is a compile-time error.  `collect![arrays: [K, V], ...]` leaves the map type to be inferred from context.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let m = collect![arrays as HashMap<_, _>: [1, "a"], [2, "b"]];
+assert_eq!(m[&2], "b");
+# }
+```
+
+```compile_fail
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let m = collect![arrays as HashMap<_, _>: [1, "a", "b"]];
+# }
+```
+
+### `take`
+
+`collect![take N: A, B, ...]` builds a `Vec` from only the first `N` elements, allocating space for exactly that many; use `collect![take N as TYPE: ...]` to select a different collection type with a `with_capacity` method.  The remaining elements are *not evaluated*.  `N` can be any `usize` expression; if it is larger than the number of elements, all of them are used.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+fn expensive() -> i32 { panic!("never called") }
+let v = collect![take 3: 1, 2, 3, expensive()];
+assert_eq!(v, [1, 2, 3]);
+# }
+```
+
+### `bounded`
+
+`collect![bounded N: ...]` builds a `VecDeque` which holds at most `N` elements: once it is full, each new element evicts the oldest one from the front, so only the last `N` are kept.  Use `collect![bounded N as TYPE: ...]` to select a different collection type, which must have `with_capacity`, `push_back` and `pop_front` methods.  The collection is created with space for `N` elements, and never grows beyond that.  Every element is still evaluated, in order, even if it is later evicted.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let d = collect![bounded 3: 1, 2, 3, 4, 5];
+assert_eq!(d, [3, 4, 5]);
+# }
+```
+
+### `sorted_pairs`
+
+`collect![sorted_pairs: K => V, ...]` builds a `Vec<(K, V)>` rather than a map, allocated once and then stably sorted by key, which must be `Ord`.  This gives a deterministic order without the overhead of a `BTreeMap`; unlike a map, entries with equal keys are all kept, in their original order.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let v = collect![sorted_pairs: 3 => "c", 1 => "a", 2 => "b"];
+assert_eq!(v, [(1, "a"), (2, "b"), (3, "c")]);
+# }
+```
+
+### `cell_values`, `mutex_values`, `rc_values`
+
+`collect![cell_values: K => V, ...]` builds a `HashMap` with each value wrapped in a `RefCell`, so that the values can be mutated through a shared reference to the map.  `mutex_values` wraps them in a `Mutex` instead, and `rc_values` in an `Rc`.  Use `collect![cell_values as MAP: ...]` (and so on) to select a different map type.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![cell_values: 1 => 10, 2 => 20];
+*m[&1].borrow_mut() += 5;
+assert_eq!(*m[&1].borrow(), 15);
+# }
+```
+
+### `self_map`
+
+`collect![self_map: A, B, ...]` builds a `HashMap` from each element to a clone of itself, which requires the element type to be `Clone`; use `collect![self_map as MAP: ...]` to select a different map type.  Each element is only evaluated once.  This is a starting point for maps which track membership, and whose values will be replaced later on.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![self_map: 1, 2, 3];
+assert_eq!(m[&2], 2);
+# }
+```
+
+### `nested`
+
+`collect![nested: K1, K2 => V, ...]` builds a two-level `HashMap<K1, HashMap<K2, V>>`, inserting each value into the inner map for `K1` under the key `K2`; inner maps are created with `Default` as they are needed.  Use `collect![nested as MAP: ...]` to select a different outer map type (which must have an `entry` API), with the inner map type as its value type.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![nested: "a", "x" => 1, "a", "y" => 2, "b", "z" => 3];
+assert_eq!(m["a"]["y"], 2);
+assert_eq!(m["b"].len(), 1);
+# }
+```
+
+### `ci_map`
+
+`collect![ci_map: K => V, ...]` builds a `HashMap<String, _>` whose keys are lowercased, which is useful for case-insensitive lookups such as HTTP headers; use `collect![ci_map as MAP: ...]` to select a different map type.  Each key can be anything which implements `AsRef<str>`, and is converted with `str::to_lowercase`, which allocates a new `String` for every key.  Keys which differ only in case collide, and as usual, the last one wins.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![ci_map: "Content-Type" => "x", "CONTENT-TYPE" => "y"];
+assert_eq!(m.len(), 1);
+assert_eq!(m["content-type"], "y");
+# }
+```
+
+### `prefix`
+
+`collect![prefix P map: K => V, ...]` builds a `HashMap<String, _>` with `P` prepended to every key, which is handy for namespacing configuration keys; use `collect![prefix P map as MAP: ...]` to select a different map type.  The prefix and each key can be anything which implements `AsRef<str>`.  The prefix is evaluated once, before any of the keys.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![prefix "db." map: "host" => "localhost", "port" => "5432"];
+assert_eq!(m["db.host"], "localhost");
+assert_eq!(m["db.port"], "5432");
+# }
+```
+
+### `obj`
+
+`collect![obj: KEY => V, ...]` builds a `HashMap<&'static str, _>` where each key is a bare identifier, which is turned into a string with `stringify!`; use `collect![obj as MAP: ...]` to select a different map type.  This saves on quotes for configuration-style maps.  Only identifiers are accepted as keys; anything else, such as a string literal, is a compile-time error.  Raw identifiers keep their prefix, so `r#type` becomes `"r#type"`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![obj: name => "alice", age => "30"];
+assert_eq!(m["name"], "alice");
+assert_eq!(m["age"], "30");
+# }
+```
+
+### `fields_of`
+
+`collect![fields_of { NAME: V, ... }]` builds a `BTreeMap<&'static str, _>` from field names to values, written like the body of a struct literal; use `collect![fields_of { ... } as MAP]` to select a different map type.  This is the same as `obj`, except for the braces and the default map type, which keeps the fields sorted by name.  It doesn't inspect an actual struct: `collect!` has no way to discover a type's fields, so each one must be listed explicitly, and all of the values must have the same type.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+struct Point { x: i32, y: i32 }
+let p = Point { x: 1, y: 2 };
+let m = collect![fields_of { y: p.y, x: p.x }];
+assert_eq!(m.into_iter().collect::<Vec<_>>(), [("x", 1), ("y", 2)]);
+# }
+```
+
+### `validate`
+
+`collect![validate (F): ...]` builds a `Vec`, calling `F` with a reference to each element before it is added.  The result is a `Result<Vec<T>, ValidationError>`: if `F` returns `false` for any element, construction stops there, none of the later elements are evaluated, and the error records the position of the element which failed.  Use `collect![validate (F) as TYPE: ...]` to select a different collection type, which must have a `with_capacity` method.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![validate (|x: &i32| *x > 0): 1, 2, 3], Ok(vec![1, 2, 3]));
+assert_eq!(collect![validate (|x: &i32| *x > 0): 1, -2, 3].unwrap_err().index, 1);
+# }
+```
+
+### `drop_eq`
+
+`collect![drop_eq S: ...]` builds a `Vec` from the elements which aren't equal to the sentinel value `S`; use `collect![drop_eq S as TYPE: ...]` to select a different collection type, which must have a `with_capacity` method.  `S` is evaluated once, before any of the elements.  Since it isn't known in advance how many elements will be dropped, the collection reserves space for all of them.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+assert_eq!(collect![drop_eq 0: 0, 1, 0, 2, 3, 0], [1, 2, 3]);
+# }
+```
+
+### `pairs`
+
+`collect![pairs: K0, V0, K1, V1, ...]` builds a `HashMap` from a flat list of alternating keys and values; use `collect![pairs as MAP: ...]` to select a different map type.  This is handy when the entries come from another macro that can't produce `=>`.  An odd number of elements is a compile-time error.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::BTreeMap;
+# fn main() {
+let m = collect![pairs as BTreeMap<_, _>: "b", 2, "a", 1];
+assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![("a", 1), ("b", 2)]);
+# }
+```
+
+```compile_fail
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m: std::collections::HashMap<&str, i32> = collect![pairs: "a", 1, "b"];
+# }
+```
+
+### `zip_arrays`
+
+`collect![zip_arrays KS, VS]` builds a `HashMap` by pairing up the elements of two parallel arrays of keys and values; use `collect![zip_arrays KS, VS as MAP]` to select a different map type.  Either argument can be anything iterable, such as an array, `Vec` or slice; arrays are consumed by value.  If the two have different lengths, the extra elements of the longer one are ignored, as with `Iterator::zip`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let ks = [1, 2, 3];
+let vs = ["a", "b", "c"];
+let m = collect![zip_arrays ks, vs];
+assert_eq!(m[&2], "b");
+# }
+```
+
+### `zip_merge`
+
+`collect![zip_merge KS, VS with (F)]` builds a `HashMap` by pairing up the items of two parallel iterables of keys and values, like `zip_arrays`, except that a key may appear more than once: each time it does, its values are combined by calling `F` with the existing value and the new one.  Use `collect![zip_merge KS, VS with (F) as MAP]` to select a different map type.  As with `Iterator::zip`, any extra items in the longer of the two are ignored.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![zip_merge ["a", "b", "a"], [1, 2, 3] with (|a, b| a + b)];
+assert_eq!((m["a"], m["b"]), (4, 2));
+# }
+```
+
+### `env_pairs`, `try_env_pairs`
+
+`collect![env_pairs: S, ...]` builds a `HashMap<&str, &str>` by splitting each string at its first `=`, in the style of environment variables or `.env` files; use `collect![env_pairs as MAP: ...]` to select a different map type.  The keys and values borrow from the original strings.  It panics if any element doesn't contain an `=`; `collect![try_env_pairs: ...]` instead returns a `Result<MAP, EnvPairError>`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let m = collect![env_pairs: "A=1", "B=x=y"];
+assert_eq!((m["A"], m["B"]), ("1", "x=y"));
+assert!(collect![try_env_pairs: "A=1", "B"].is_err());
+# }
+```
+
+### Arrays
+
+`collect![as [T; N]: ...]` builds a fixed-size array from a list of elements.  `N` can be any constant expression, such as `2 + 1` or the name of a `const`, and if it doesn't match the number of elements, you'll get the compiler's usual array length error.  Spreads and maps aren't supported for arrays.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+const N: usize = 2;
+assert_eq!(collect![as [i32; N + 1]: 1, 2, 3], [1, 2, 3]);
+# }
+```
+
+```compile_fail
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let arr = collect![as [i32; 2 + 2]: 1, 2, 3];
+# }
+```
+
+### Shared and boxed slices
+
+`collect![as Arc<[T]>: ...]` builds a `Vec<T>` in a single allocation, then converts it; the same works for `Rc<[T]>` and `Box<[T]>`, or any other `P<[T]>` which implements `From<Vec<T>>`.  The pointer type must be written as a plain path (such as `Arc` or `std::rc::Rc`), without a leading `::`.  Converting to a `Box<[T]>` reuses the `Vec`'s buffer, since its capacity is exact, but `Arc` and `Rc` need room for their reference counts and so copy the elements into a second allocation.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::sync::Arc;
+# fn main() {
+let xs = collect![as Arc<[i32]>: 1, 2, 3];
+assert_eq!(&xs[..], &[1, 2, 3]);
+# }
+```
+
+### Boxed strings
+
+`collect![as Box<str>: S, ...]` concatenates string slices into a `Box<str>`.  Unlike building a `String`, which only knows how many pieces there are, this adds up their lengths first, so the intermediate `String` is allocated exactly once and converts to a `Box<str>` without reallocating.  Each element must be a `&str` (or something which coerces to one, like `&String`), and spreads aren't supported.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let s = collect![as Box<str>: "a", "bc"];
+assert_eq!(&*s, "abc");
+# }
+```
+
 ## Details
 
-The macro supports any collection which implements both the [`Default`][Default] and [`Extend`][Extend] traits.  Specifically, it creates a new, empty collection using `Default`, then calls `Extend` once for each element.
+The macro supports any collection which implements both the [`Default`][Default] and [`Extend`][Extend] traits.  Specifically, it creates a new, empty collection using `Default`, then calls `Extend` once with all of the elements.  As such, every element in a literal list must have the same type.
+
+This includes collections with extra type parameters, such as a `HashMap<K, V, S>` with a custom hasher, provided that they still implement `Default`.  In the case of `HashMap` and `HashSet`, that means `S` must implement `Default`; if it doesn't, the compiler will complain that the trait bound `HashMap<K, V, S>: Default` is not satisfied.
+
+Single-allocation construction is tested and guaranteed for the following standard containers:
+
+* [`HashMap`](http://doc.rust-lang.org/std/collections/struct.HashMap.html)
+* [`HashSet`](http://doc.rust-lang.org/std/collections/struct.HashSet.html)
+* [`String`](http://doc.rust-lang.org/std/string/struct.String.html)
+* [`Vec`](http://doc.rust-lang.org/std/vec/struct.Vec.html)
+* [`VecDeque`](http://doc.rust-lang.org/std/collections/struct.VecDeque.html)
+
+`BTreeSet` and `BTreeMap` don't allocate up front, but they *can* be built much faster from a whole batch of elements at once.  So, when the type after `as` is written as a bare `BTreeSet<...>` or `BTreeMap<...>` and there are no spreads, the elements are passed to a single call to `FromIterator::from_iter`, which sorts them and builds the tree in bulk.  The result is exactly the same as inserting them one at a time; in particular, the last of several equal keys still wins.
+
+Element expressions (including both halves of each map entry, and spread sources) are always evaluated exactly once, in the order in which they are written.  Any form which deviates from this, such as one which skips elements entirely, says so explicitly.
+
+In general, single-allocation construction is done by providing the number of elements through the [`Iterator::size_hint`][Iterator::size_hint] of the *first* call to `Extend`.  The expectation is that the collection will, if possible, pre-allocate enough space for all the elements when it goes to insert the first.
+
+As an example, here is a simplified version of the `Extend` implementation for `Vec`:
+
+```ignore
+impl<T> Extend<T> for Vec<T> {
+    #[inline]
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iterable: I) {
+        let mut iterator = iterable.into_iter();
+        while let Some(element) = iterator.next() {
+            let len = self.len();
+            if len == self.capacity() {
+                let (lower, _) = iterator.size_hint();
+                self.reserve(lower.saturating_add(1));
+            }
+            self.push(element);
+        }
+    }
+}
+```
+
+[Default]: http://doc.rust-lang.org/std/default/trait.Default.html
+[Extend]: http://doc.rust-lang.org/std/iter/trait.Extend.html
+[Iterator::size_hint]: http://doc.rust-lang.org/std/iter/trait.Iterator.html#method.size_hint
+*/
+
+#![cfg_attr(feature = "nightly", feature(allocator_api, map_try_insert))]
+
+#[cfg(feature = "once_cell")]
+#[doc(hidden)]
+pub extern crate once_cell as __once_cell;
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FromIterator;
+
+mod builder;
+mod error;
+mod parse;
+
+pub use builder::CollectBuilder;
+pub use error::{CheckError, DuplicateKeyError, ParseError, ValidationError};
+pub use parse::{BitsError, EnvPairError, HexError};
+#[doc(hidden)] pub use parse::{decode_bits, decode_hex, split_env_pair};
+
+/**
+This macro can be used to easily construct arbitrary collections, including `Vec`, `String`, and `HashMap`.  It also endeavours to construct the collection with a single allocation, where possible.
+
+For more details, see [the crate documentation](./index.html).
+*/
+#[macro_export]
+macro_rules! collect {
+    /*
+    Internal rules.
+    */
+
+    (@count_tts $($tts:tt)*) => {
+        0usize $(+ collect!(@replace_expr $tts 1usize))*
+    };
+
+    (@replace_expr $_tt:tt $sub:expr) => {
+        $sub
+    };
+
+    // Unwraps a single token tree argument, stripping any parentheses so they don't trip the `unused_parens` lint.
+    (@arg ($e:expr)) => {
+        $e
+    };
+
+    (@arg $e:tt) => {
+        $e
+    };
+
+    (@collect
+        ty: $col_ty:ty,
+        es: [$v0:expr, $($vs:expr),* $(,)*],
+        // `cb` is an expression that is inserted after each "step" in constructing the collection.  It largely exists for testing purposes.
+        cb: ($col:ident) $cb:expr,
+    ) => {
+        {
+            let mut $col: $col_ty = ::std::default::Default::default();
+
+            $cb;
+
+            // All elements go into an array, so the collection sees them in a single `extend` call whose iterator knows its exact length up front.
+            ::std::iter::Extend::extend(&mut $col, ::std::iter::IntoIterator::into_iter([$v0, $($vs),*]));
+
+            $cb;
+
+            $col
+        }
+    };
+
+    /*
+    `@seq` munches a comma-terminated list of elements, tagging each one as either a literal element (which may be a `k => v` map entry) or a spread.  A spread can't be ruled out with a single pattern, since `..x` is also a perfectly good `expr`; instead, each step looks for a spread in any of the next sixteen positions and otherwise takes all sixteen elements at once.  Once fewer than sixteen are left, the rest are tagged in one go.  That's one level of recursion per sixteen elements, so literal tables well over a thousand elements long still fit under the default recursion limit.
+
+    `sm` is a group of tokens appended to every spread source (for example, `[.cloned()]`).  `sp` records whether any spread has been seen; if not, construction goes through `@collect` exactly as before.
+    */
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $sp:ident, ps: [$($ps:tt)*], es: [$(,)*]) => {
+        collect!(@seq_end ty: $col_ty, cap: $cap, sm: $sm, sp: $sp, ps: [$($ps)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [.. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, $e10:expr $(=> $v10:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (lit $e10 $(=> $v10)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, $e10:expr $(=> $v10:expr)*, $e11:expr $(=> $v11:expr)*,
+             .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (lit $e10 $(=> $v10)*) (lit $e11 $(=> $v11)*)
+                         (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, $e10:expr $(=> $v10:expr)*, $e11:expr $(=> $v11:expr)*,
+             $e12:expr $(=> $v12:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (lit $e10 $(=> $v10)*) (lit $e11 $(=> $v11)*)
+                         (lit $e12 $(=> $v12)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, $e10:expr $(=> $v10:expr)*, $e11:expr $(=> $v11:expr)*,
+             $e12:expr $(=> $v12:expr)*, $e13:expr $(=> $v13:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (lit $e10 $(=> $v10)*) (lit $e11 $(=> $v11)*)
+                         (lit $e12 $(=> $v12)*) (lit $e13 $(=> $v13)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, $e10:expr $(=> $v10:expr)*, $e11:expr $(=> $v11:expr)*,
+             $e12:expr $(=> $v12:expr)*, $e13:expr $(=> $v13:expr)*, $e14:expr $(=> $v14:expr)*, .. $s:expr, $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: yes,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (lit $e10 $(=> $v10)*) (lit $e11 $(=> $v11)*)
+                         (lit $e12 $(=> $v12)*) (lit $e13 $(=> $v13)*) (lit $e14 $(=> $v14)*) (spread $s)],
+            es: [$($es)*])
+    };
+
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $sp:ident, ps: [$($ps:tt)*],
+        es: [$e0:expr $(=> $v0:expr)*, $e1:expr $(=> $v1:expr)*, $e2:expr $(=> $v2:expr)*, $e3:expr $(=> $v3:expr)*,
+             $e4:expr $(=> $v4:expr)*, $e5:expr $(=> $v5:expr)*, $e6:expr $(=> $v6:expr)*, $e7:expr $(=> $v7:expr)*,
+             $e8:expr $(=> $v8:expr)*, $e9:expr $(=> $v9:expr)*, $e10:expr $(=> $v10:expr)*, $e11:expr $(=> $v11:expr)*,
+             $e12:expr $(=> $v12:expr)*, $e13:expr $(=> $v13:expr)*, $e14:expr $(=> $v14:expr)*, $e15:expr $(=> $v15:expr)*,
+             $($es:tt)*]
+    ) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: $sp,
+            ps: [$($ps)* (lit $e0 $(=> $v0)*) (lit $e1 $(=> $v1)*) (lit $e2 $(=> $v2)*) (lit $e3 $(=> $v3)*)
+                         (lit $e4 $(=> $v4)*) (lit $e5 $(=> $v5)*) (lit $e6 $(=> $v6)*) (lit $e7 $(=> $v7)*)
+                         (lit $e8 $(=> $v8)*) (lit $e9 $(=> $v9)*) (lit $e10 $(=> $v10)*) (lit $e11 $(=> $v11)*)
+                         (lit $e12 $(=> $v12)*) (lit $e13 $(=> $v13)*) (lit $e14 $(=> $v14)*) (lit $e15 $(=> $v15)*)],
+            es: [$($es)*])
+    };
+
+    // Fewer than sixteen elements are left and none of them is a spread, so they can all be tagged at once.
+    (@seq ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $sp:ident, ps: [$($ps:tt)*],
+        es: [$($e:expr $(=> $v:expr)*),+ $(,)*]
+    ) => {
+        collect!(@seq_end ty: $col_ty, cap: $cap, sm: $sm, sp: $sp, ps: [$($ps)* $((lit $e $(=> $v)*))+])
+    };
+
+    // Maps implement `FromIterator` by taking tuples, so we just need to rewrite each `k => v` as `(k, v)`.
+    (@entry $e:expr) => {
+        $e
+    };
+
+    (@entry $k:expr => $v:expr) => {
+        ($k, $v)
+    };
+
+    (@seq_end ty: $col_ty:ty, cap: (), sm: $_sm:tt, sp: no, ps: [$((lit $es:expr $(=> $vs:expr)*))*]) => {
+        collect!(
+            @collect
+            ty: $col_ty,
+            es: [$(collect!(@entry $es $(=> $vs)*),)*],
+            cb: (col) (),
+        )
+    };
+
+    // B-trees build faster in bulk, from a single call to `FromIterator`.
+    (@seq_end ty: $col_ty:ty, cap: bulk, sm: $_sm:tt, sp: no, ps: [$((lit $es:expr $(=> $vs:expr)*))*]) => {
+        {
+            let col: $col_ty = ::std::iter::FromIterator::from_iter(::std::iter::IntoIterator::into_iter([$(collect!(@entry $es $(=> $vs)*)),*]));
+            col
+        }
+    };
+
+    (@seq_end ty: $col_ty:ty, cap: $cap:tt, sm: $sm:tt, sp: $_sp:ident, ps: [$($ps:tt)*]) => {
+        {
+            // Chaining every piece into a single iterator means the collection sees the combined size hint on its first (and only) call to `Extend`.
+            let iter = ::std::iter::empty()
+                $(.chain(collect!(@seq_piece $sm $ps)))*;
+            let mut col: $col_ty = collect!(@init $col_ty, $cap, ::std::iter::Iterator::size_hint(&iter).0);
+            ::std::iter::Extend::extend(&mut col, iter);
+            col
+        }
+    };
+
+    /*
+    `@init` creates an empty collection.  `cap` is either `()` or `bulk`, in which case the collection is created with `Default`, or `($n => $e)`, in which case it is created with `with_capacity($e)`, where `$n` is bound to the number of elements that are known to be on the way.
+    */
+
+    (@init $col_ty:ty, (), $_hint:expr) => {
+        ::std::default::Default::default()
+    };
+
+    (@init $col_ty:ty, bulk, $_hint:expr) => {
+        ::std::default::Default::default()
+    };
+
+    (@init $col_ty:ty, ($n:ident => $e:expr), $hint:expr) => {
+        {
+            let $n: usize = $hint;
+            <$col_ty>::with_capacity($e)
+        }
+    };
+
+    (@seq_piece $_sm:tt (lit $e:expr $(=> $v:expr)*)) => {
+        ::std::option::Option::Some(collect!(@entry $e $(=> $v)*))
+    };
+
+    (@seq_piece [$($sm:tt)*] (spread $e:expr)) => {
+        ::std::iter::IntoIterator::into_iter($e) $($sm)*
+    };
+
+    // The optional first element is matched with an outer repetition, so that separators only go *between* elements.
+    (@fmt_join $sep:tt, $col_ty:ty, [$($e0:expr $(, $es:expr)*)*]) => {
+        {
+            let sep = collect!(@arg $sep);
+            let mut col = <$col_ty>::with_capacity(collect!(@count_tts $(($e0) $(($es))*)*));
+            $(
+                collect!(@write_fmt col, "{}", $e0);
+                $(
+                    collect!(@write_fmt col, "{}{}", sep, $es);
+                )*
+            )*
+            col
+        }
+    };
+
+    (@write_fmt $col:ident, $($args:tt)*) => {
+        ::std::fmt::Write::write_fmt(&mut $col, format_args!($($args)*))
+            .expect("a Display implementation returned an error unexpectedly")
+    };
+
+    (@array_entry [$k:expr, $v:expr $(,)*]) => {
+        ($k, $v)
+    };
+
+    (@array_entry $other:tt) => {
+        compile_error!(concat!("collect![arrays]: expected a two-element array `[key, value]`, found `", stringify!($other), "`"))
+    };
+
+    (@wrap_values $new:path, $col_ty:ty, $($ks:expr => $vs:expr),* $(,)*) => {
+        collect![as $col_ty: $($ks => $new($vs)),*]
+    };
+
+    // `pad_to` reserves space for the padding too, unless another modifier is in charge of allocation.
+    (@pad_build $len:ident, as $($rest:tt)*) => {
+        collect![ring $len as $($rest)*]
+    };
+
+    (@pad_build $len:ident, $($rest:tt)*) => {
+        collect![$($rest)*]
+    };
+
+    (@case $push:ident, $col_ty:ty, [$($es:expr),+]) => {
+        {
+            let pieces = [$($es),+];
+            let mut col: $col_ty = ::std::default::Default::default();
+            // Case conversion can change the length, so this is only an estimate.
+            col.reserve(pieces.iter().map($crate::CasePiece::byte_len).sum());
+            for piece in ::std::iter::IntoIterator::into_iter(pieces) {
+                $crate::CasePiece::$push(piece, &mut col);
+            }
+            col
+        }
+    };
+
+    // Directly before `as`, the sentinel's slot can be reserved along with the elements.
+    (@terminate_build as $($rest:tt)*) => {
+        collect![reserve 1 as $($rest)*]
+    };
+
+    (@terminate_build $($rest:tt)*) => {
+        collect![$($rest)*]
+    };
+
+    /*
+    `@interleave` takes one element from each group in turn, until both are empty.
+    */
+
+    (@interleave $col_ty:ty, [$($done:tt)*], (), ()) => {
+        collect![as $col_ty: $($done)*]
+    };
+
+    (@interleave $col_ty:ty, [$($done:tt)*], ($a:expr $(, $as:expr)*), ($b:expr $(, $bs:expr)*)) => {
+        collect!(@interleave $col_ty, [$($done)* $a, $b,], ($($as),*), ($($bs),*))
+    };
+
+    (@interleave $col_ty:ty, $done:tt, $_as:tt, $_bs:tt) => {
+        compile_error!("collect![interleave]: both groups must have the same number of elements")
+    };
+
+    /*
+    `@pairs` regroups a flat list of alternating keys and values into `key => value` entries, four at a time to keep the recursion shallow.  Every element is followed by a comma.
+    */
+
+    (@pairs $col_ty:ty, [$($done:tt)*],) => {
+        collect![as $col_ty: $($done)*]
+    };
+
+    (@pairs $col_ty:ty, [$($done:tt)*],
+        $k0:expr, $v0:expr, $k1:expr, $v1:expr, $k2:expr, $v2:expr, $k3:expr, $v3:expr, $($rest:tt)*) => {
+        collect!(@pairs $col_ty, [$($done)* $k0 => $v0, $k1 => $v1, $k2 => $v2, $k3 => $v3,], $($rest)*)
+    };
+
+    (@pairs $col_ty:ty, [$($done:tt)*], $k:expr, $v:expr, $($rest:tt)*) => {
+        collect!(@pairs $col_ty, [$($done)* $k => $v,], $($rest)*)
+    };
+
+    (@pairs $col_ty:ty, [$($done:tt)*], $_k:expr,) => {
+        compile_error!("collect![pairs]: odd number of elements; expected alternating keys and values")
+    };
+
+    /*
+    `@post` builds the collection described by `$rest`, then runs `$body` against it.  This is used to implement modifiers which act on the finished collection.
+    */
+
+    (@post ($col:ident) [$($rest:tt)*] $($body:tt)*) => {
+        {
+            let mut $col = collect![$($rest)*];
+            $($body)*
+            $col
+        }
+    };
+
+    /*
+    `@mods` accumulates modifiers which control the initial capacity of the collection (`cap`) or apply to spread sources (`sm`), up to the `as` clause.  `src` holds the original input when it started with a modifier, so that it can be treated as a list of elements instead if it turns out not to be one.
+    */
+
+    (@mods src: $_src:tt, cap: $cap:tt, sm: $_sm:tt, as $col_ty:ty $(:)*) => {
+        {
+            let col: $col_ty = collect!(@init $col_ty, $cap, 0);
+            col
+        }
+    };
+
+    (@mods src: $_src:tt, cap: $cap:tt, sm: $sm:tt, as $col_ty:ty: $($es:tt)+) => {
+        collect!(@seq ty: $col_ty, cap: $cap, sm: $sm, sp: no, ps: [], es: [$($es)+ ,])
+    };
+
+    (@mods src: $src:tt, cap: $cap:tt, sm: [$($sm:tt)*], cloned $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: $cap, sm: [$($sm)* .cloned()], $($rest)*)
+    };
+
+    (@mods src: $src:tt, cap: $cap:tt, sm: [$($sm:tt)*], limit $n:tt $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: $cap, sm: [$($sm)* .take(collect!(@arg $n))], $($rest)*)
+    };
+
+    (@mods src: $src:tt, cap: $_cap:tt, sm: $sm:tt, ring $n:tt $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: (hint => ::std::cmp::max(hint, collect!(@arg $n))), sm: $sm, $($rest)*)
+    };
+
+    (@mods src: $src:tt, cap: $_cap:tt, sm: $sm:tt, reserve $n:tt $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: (hint => hint + collect!(@arg $n)), sm: $sm, $($rest)*)
+    };
+
+    (@mods src: $src:tt, cap: $_cap:tt, sm: $sm:tt, pow2 $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: (hint => if hint == 0 { 0 } else { hint.next_power_of_two() }), sm: $sm, $($rest)*)
+    };
+
+    (@mods src: $src:tt, cap: $_cap:tt, sm: $sm:tt, cap_from $n:tt $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: (_hint => collect!(@arg $n)), sm: $sm, $($rest)*)
+    };
+
+    (@mods src: $src:tt, cap: $_cap:tt, sm: $sm:tt, headroom $($rest:tt)*) => {
+        collect!(@mods src: $src, cap: (hint => hint * 8 / 7 + 1), sm: $sm, $($rest)*)
+    };
+
+    // Whatever the modifier words were, they weren't followed by more modifiers or an `as` clause, so they must have been the start of an ordinary element, such as `limit + 1`.
+    (@mods src: [$($src:tt)+], cap: $_cap:tt, sm: $_sm:tt, $($_rest:tt)*) => {
+        collect![as _: $($src)+]
+    };
+
+    /*
+    Public rules.
+    */
+
+    // Short-hands for initialising an empty collection.
+    [] => {
+        collect![as _:]
+    };
+
+    // Arrays are built directly, so the compiler checks the element count against the length.
+    [as [$t:ty; $n:expr] $(:)*] => {
+        {
+            let arr: [$t; $n] = [];
+            arr
+        }
+    };
+
+    [as [$t:ty; $n:expr]: $($es:expr),+ $(,)*] => {
+        {
+            let arr: [$t; $n] = [$($es),+];
+            arr
+        }
+    };
+
+    // Boxed strings are built from a `String` with room for every byte.
+    [as Box<str> $(:)*] => {
+        ::std::string::String::new().into_boxed_str()
+    };
+
+    [as Box<str>: $($es:expr),+ $(,)*] => {
+        {
+            let pieces: [&str; collect!(@count_tts $(($es))+)] = [$($es),+];
+            let mut s = ::std::string::String::with_capacity(pieces.iter().map(|p| p.len()).sum());
+            for p in &pieces {
+                s.push_str(p);
+            }
+            s.into_boxed_str()
+        }
+    };
+
+    // Shared and boxed slices can't be extended, so build a `Vec` and convert it.
+    [as $($ptr:ident)::+ <[$t:ty]> $(:)*] => {
+        <$($ptr)::+<[$t]> as ::std::convert::From<::std::vec::Vec<$t>>>::from(::std::vec::Vec::new())
+    };
+
+    [as $($ptr:ident)::+ <[$t:ty]>: $($es:tt)+] => {
+        {
+            let vec: ::std::vec::Vec<$t> = collect![as ::std::vec::Vec<$t>: $($es)+];
+            <$($ptr)::+<[$t]> as ::std::convert::From<::std::vec::Vec<$t>>>::from(vec)
+        }
+    };
+
+    [as $col_ty:ty] => {
+        collect![as $col_ty:]
+    };
+
+    [as $col_ty:ty:] => {
+        {
+            let col: $col_ty = ::std::default::Default::default();
+            col
+        }
+    };
+
+    // Initialise a collection with space for an expected number of distinct elements.  Filtering a spread source drops the lower bound of its size hint, so the collection doesn't reserve any more space when it's extended.
+    [as $col_ty:ty: expected($n:expr); $($es:tt)*] => {
+        collect!(@mods src: [], cap: (_hint => $n), sm: [.filter(|_| true)], as $col_ty: $($es)*)
+    };
+
+    // Initialise a B-tree set or map in bulk.  The type is re-emitted as written, so that it resolves to whatever the caller imported.
+    [as BTreeSet<$t:ty>: $($es:tt)+] => {
+        collect!(@seq ty: BTreeSet<$t>, cap: bulk, sm: [], sp: no, ps: [], es: [$($es)+ ,])
+    };
+
+    [as BTreeMap<$k:ty, $v:ty>: $($es:tt)+] => {
+        collect!(@seq ty: BTreeMap<$k, $v>, cap: bulk, sm: [], sp: no, ps: [], es: [$($es)+ ,])
+    };
+
+    // Initialise a sequence or map with a constrained container type.  Either may include spreads.
+    [as $col_ty:ty: $($es:tt)+] => {
+        collect!(@mods src: [], cap: (), sm: [], as $col_ty: $($es)+)
+    };
+
+    // An identifier which is cast or has a method called on it is just an element, even if it shares its name with a modifier or form.  On its own, `WORD as TYPE` is an empty collection, unless `TYPE` is a primitive (which can't be a collection, but can be cast to).
+    [$e:ident as $t:ty, $($rest:tt)*] => {
+        collect![as _: $e as $t, $($rest)*]
+    };
+
+    [$e:ident as $t:ty => $($rest:tt)*] => {
+        collect![as _: $e as $t => $($rest)*]
+    };
+
+    [$e:ident . $($rest:tt)*] => {
+        collect![as _: $e . $($rest)*]
+    };
+
+    [$e:ident as i8] => {
+        collect![as _: $e as i8]
+    };
+
+    [$e:ident as i16] => {
+        collect![as _: $e as i16]
+    };
+
+    [$e:ident as i32] => {
+        collect![as _: $e as i32]
+    };
+
+    [$e:ident as i64] => {
+        collect![as _: $e as i64]
+    };
+
+    [$e:ident as i128] => {
+        collect![as _: $e as i128]
+    };
+
+    [$e:ident as isize] => {
+        collect![as _: $e as isize]
+    };
+
+    [$e:ident as u8] => {
+        collect![as _: $e as u8]
+    };
+
+    [$e:ident as u16] => {
+        collect![as _: $e as u16]
+    };
+
+    [$e:ident as u32] => {
+        collect![as _: $e as u32]
+    };
+
+    [$e:ident as u64] => {
+        collect![as _: $e as u64]
+    };
+
+    [$e:ident as u128] => {
+        collect![as _: $e as u128]
+    };
+
+    [$e:ident as usize] => {
+        collect![as _: $e as usize]
+    };
+
+    [$e:ident as f32] => {
+        collect![as _: $e as f32]
+    };
+
+    [$e:ident as f64] => {
+        collect![as _: $e as f64]
+    };
+
+    [$e:ident as char] => {
+        collect![as _: $e as char]
+    };
+
+    // Initialise a map from keys alone, with default values.
+    [keys as $col_ty:ty: $($ks:expr),+ $(,)*] => {
+        collect![as $col_ty: $($ks => ::std::default::Default::default()),+]
+    };
+
+    [keys: $($es:tt)*] => {
+        collect![keys as _: $($es)*]
+    };
+
+    // Initialise a map from two-element arrays.
+    [arrays as $col_ty:ty: $($arrs:tt),+ $(,)*] => {
+        collect![as $col_ty: $(collect!(@array_entry $arrs)),+]
+    };
+
+    [arrays: $($es:tt)*] => {
+        collect![arrays as _: $($es)*]
+    };
+
+    // Sort the finished collection.
+    [sorted $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] col.sort();)
+    };
+
+    [sorted_by $f:tt $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] col.sort_by(collect!(@arg $f));)
+    };
+
+    [sort_by_key $f:tt $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] col.sort_by_key(collect!(@arg $f));)
+    };
+
+    [rotate $n:tt $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*]
+            if !col.is_empty() {
+                let n = collect!(@arg $n) % col.len();
+                col.rotate_left(n);
+            }
+        )
+    };
+
+    [pad_to $len:tt with $fill:tt $m:ident $($rest:tt)*] => {
+        {
+            let len: usize = collect!(@arg $len);
+            let fill = collect!(@arg $fill);
+            let mut col = collect!(@pad_build len, $m $($rest)*);
+            let count = col.len();
+            if count < len {
+                ::std::iter::Extend::extend(&mut col, ::std::iter::Iterator::take(::std::iter::repeat(fill), len - count));
+            }
+            col
+        }
+    };
+
+    [terminate_with $sentinel:tt: $($es:tt)*] => {
+        collect![terminate_with $sentinel as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    [terminate_with $sentinel:tt $m:ident $($rest:tt)*] => {
+        {
+            let sentinel = collect!(@arg $sentinel);
+            let mut col = collect!(@terminate_build $m $($rest)*);
+            ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some(sentinel));
+            col
+        }
+    };
+
+    [try_check $pred:tt: $($es:tt)*] => {
+        collect![try_check $pred as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    [try_check $pred:tt $m:ident $($rest:tt)*] => {
+        $crate::check(collect![$m $($rest)*], collect!(@arg $pred))
+    };
+
+    [check $pred:tt: $($es:tt)*] => {
+        collect![check $pred as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    [check $pred:tt $m:ident $($rest:tt)*] => {
+        match collect![try_check $pred $m $($rest)*] {
+            ::std::result::Result::Ok(col) => col,
+            ::std::result::Result::Err(e) => panic!("collect![check]: {}", e),
+        }
+    };
+
+    [rev $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] $crate::Reverse::reverse(&mut col);)
+    };
+
+    [shrink $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] col.shrink_to_fit();)
+    };
+
+    [with_hash as $col_ty:ty $(:)*] => {
+        $crate::HashOnInsert::finish(collect![as $crate::HashOnInsert<$col_ty>])
+    };
+
+    [with_hash as $col_ty:ty: $($es:tt)+] => {
+        $crate::HashOnInsert::finish(collect![as $crate::HashOnInsert<$col_ty>: $($es)+])
+    };
+
+    [timed $m:ident $($rest:tt)*] => {
+        {
+            let start = ::std::time::Instant::now();
+            let col = collect![$m $($rest)*];
+            (col, start.elapsed())
+        }
+    };
+
+    [dedup_adjacent: $($es:tt)*] => {
+        collect![dedup_adjacent as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    [dedup_adjacent $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] col.dedup();)
+    };
+
+    [dedup_by_key $f:tt: $($es:tt)*] => {
+        collect![dedup_by_key $f as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    [dedup_by_key $f:tt $m:ident $($rest:tt)*] => {
+        collect!(@post (col) [$m $($rest)*] $crate::dedup_by_key(&mut col, collect!(@arg $f));)
+    };
+
+    // Build the collection as normal, checking that it only allocated once.
+    [assert_single_alloc as $col_ty:ty $(:)*] => {
+        collect![as $col_ty]
+    };
+
+    [assert_single_alloc as $col_ty:ty: $($ks:expr => $vs:expr),+ $(,)*] => {
+        collect![assert_single_alloc as $col_ty: $(($ks, $vs)),+]
+    };
+
+    [assert_single_alloc as $col_ty:ty: $($es:expr),+ $(,)*] => {
+        {
+            // `cb` runs before and after the elements are added, so this ends up holding the initial and final capacities.
+            let mut caps = (0usize, 0usize);
+            let col = collect!(
+                @collect
+                ty: $col_ty,
+                es: [$($es,)+],
+                cb: (col) { caps = (caps.1, col.capacity()); },
+            );
+            if cfg!(debug_assertions) {
+                // A single allocation ends up with exactly the capacity of an empty collection which reserved space for every element.
+                let count = collect!(@count_tts $(($es))+);
+                let mut reserved = $crate::empty_like(&col);
+                reserved.reserve(count);
+                if caps.1 != reserved.capacity() {
+                    panic!("collect![assert_single_alloc]: capacity went from {} to {}, but a single allocation for {} elements gives {}",
+                        caps.0, caps.1, count, reserved.capacity());
+                }
+            }
+            col
+        }
+    };
+
+    // Build the collection inside a single, non-inlined function.
+    [outline as $col_ty:ty: $($ks:expr => $vs:expr),+ $(,)*] => {
+        collect![outline as $col_ty: $(($ks, $vs)),+]
+    };
+
+    [outline as $col_ty:ty: $($es:expr),+ $(,)*] => {
+        {
+            let col: $col_ty = $crate::build_from_array([$($es),+]);
+            col
+        }
+    };
+
+    // Extend an existing collection in place.
+    [into $target:expr; $($ks:expr => $vs:expr),+ $(,)*] => {
+        collect![into $target; $(($ks, $vs)),+]
+    };
+
+    [into $target:expr; $($es:expr),* $(,)*] => {
+        ::std::iter::Extend::extend(&mut $target, ::std::iter::IntoIterator::into_iter([$($es),*]))
+    };
+
+    // Allocate the collection with a custom allocator.
+    [in $alloc:tt as $col_ty:ty $(: $($es:expr),* $(,)*)*] => {
+        {
+            let mut col = <$col_ty as $crate::WithCapacityIn<_>>::with_capacity_in(
+                collect!(@count_tts $($(($es))*)*),
+                collect!(@arg $alloc)
+            );
+            $($(
+                ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some($es));
+            )*)*
+            col
+        }
+    };
+
+    // Allocate the collection before evaluating any of the elements.
+    [prealloc as $col_ty:ty: $($ks:expr => $vs:expr),+ $(,)*] => {
+        collect![prealloc as $col_ty: $(($ks, $vs)),+]
+    };
+
+    [prealloc as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let mut col = <$col_ty>::with_capacity(collect!(@count_tts $(($es))*));
+            $(
+                ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some($es));
+            )*
+            col
+        }
+    };
+
+    // Build a collection with `FromIterator` alone, for types which don't implement `Default` and `Extend`.
+    [via_from_iter as $col_ty:ty: $($ks:expr => $vs:expr),+ $(,)*] => {
+        collect![via_from_iter as $col_ty: $(($ks, $vs)),+]
+    };
+
+    [via_from_iter as $col_ty:ty $(: $($es:expr),* $(,)*)*] => {
+        {
+            // Arrays have an exactly-sized iterator, so the collection can still allocate up front.
+            let col: $col_ty = ::std::iter::FromIterator::from_iter(
+                ::std::iter::IntoIterator::into_iter([$($($es),*)*])
+            );
+            col
+        }
+    };
+
+    // Initialise a collection from the contents of another; this is just a single spread.
+    [from $src:tt as $col_ty:ty] => {
+        collect![as $col_ty: ..collect!(@arg $src)]
+    };
+
+    [from $src:expr] => {
+        collect![as _: ..$src]
+    };
+
+    // Clone the elements of every spread source.
+    [cloned $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [cloned $m $($rest)*], cap: (), sm: [], cloned $m $($rest)*)
+    };
+
+    [limit $n:tt $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [limit $n $m $($rest)*], cap: (), sm: [], limit $n $m $($rest)*)
+    };
+
+    // Pre-allocate a specific capacity.
+    [ring $n:tt $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [ring $n $m $($rest)*], cap: (), sm: [], ring $n $m $($rest)*)
+    };
+
+    // Pre-allocate extra capacity beyond the elements.
+    [reserve $n:tt $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [reserve $n $m $($rest)*], cap: (), sm: [], reserve $n $m $($rest)*)
+    };
+
+    [pow2 $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [pow2 $m $($rest)*], cap: (), sm: [], pow2 $m $($rest)*)
+    };
+
+    [headroom $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [headroom $m $($rest)*], cap: (), sm: [], headroom $m $($rest)*)
+    };
+
+    [cap_from $n:tt $m:ident $($rest:tt)*] => {
+        collect!(@mods src: [cap_from $n $m $($rest)*], cap: (), sm: [], cap_from $n $m $($rest)*)
+    };
+
+    // Initialise a map from a sequence of keys and a value closure.
+    [range_map as $col_ty:ty: $keys:expr => $f:expr] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            ::std::iter::Extend::extend(&mut col, $crate::MapKeys::new($keys, $f));
+            col
+        }
+    };
+
+    [range_map $keys:expr => $f:expr] => {
+        collect![range_map as ::std::collections::BTreeMap<_, _>: $keys => $f]
+    };
+
+    [map_values $f:tt over $keys:tt as $col_ty:ty] => {
+        collect![range_map as $col_ty: collect!(@arg $keys) => collect!(@arg $f)]
+    };
+
+    [map_values $f:tt over $keys:expr] => {
+        collect![map_values $f over ($keys) as ::std::collections::HashMap<_, _>]
+    };
+
+    // Initialise a map from entries generated by a closure over an index range.
+    [table $range:tt as $col_ty:ty: $f:expr] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            ::std::iter::Extend::extend(
+                &mut col,
+                ::std::iter::Iterator::map(::std::iter::IntoIterator::into_iter(collect!(@arg $range)), $f)
+            );
+            col
+        }
+    };
+
+    [table $range:tt: $f:expr] => {
+        collect![table $range as ::std::collections::HashMap<_, _>: $f]
+    };
+
+    // Initialise a sequence by calling a function a fixed number of times.
+    [gen $n:tt as $col_ty:ty: $f:expr] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            ::std::iter::Extend::extend(
+                &mut col,
+                ::std::iter::Iterator::take(::std::iter::repeat_with($f), collect!(@arg $n))
+            );
+            col
+        }
+    };
+
+    [gen $n:tt: $f:expr] => {
+        collect![gen $n as ::std::vec::Vec<_>: $f]
+    };
+
+    // Initialise a map from each distinct element to the number of times it appears.
+    [ordered_counts as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            $(
+                *col.entry($es).or_insert(0usize) += 1;
+            )*
+            col
+        }
+    };
+
+    [ordered_counts: $($es:tt)*] => {
+        collect![ordered_counts as ::std::collections::BTreeMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map from fallible values, stopping at the first error.
+    [try_values as $col_ty:ty: $($ks:expr => $vs:expr),* $(,)*] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            let mut error = ::std::option::Option::None;
+            // Each entry claims to be followed by the rest, so the map can reserve space for all of them up front.
+            let mut remaining: usize = collect!(@count_tts $(($ks))*);
+            $(
+                if error.is_none() {
+                    let k = $ks;
+                    match $vs {
+                        ::std::result::Result::Ok(v) => {
+                            ::std::iter::Extend::extend(&mut col, $crate::SizeHintIter {
+                                item: ::std::option::Option::Some((k, v)),
+                                count: remaining,
+                            });
+                            remaining -= 1;
+                        },
+                        ::std::result::Result::Err(e) => error = ::std::option::Option::Some(e),
+                    }
+                }
+            )*
+            match error {
+                ::std::option::Option::Some(e) => ::std::result::Result::Err(e),
+                ::std::option::Option::None => ::std::result::Result::Ok(col),
+            }
+        }
+    };
+
+    [try_values: $($es:tt)*] => {
+        collect![try_values as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a sequence by calling a fallible function for each index.
+    [try_gen $range:tt as $col_ty:ty: $f:expr] => {
+        {
+            let result: ::std::result::Result<$col_ty, _> = $crate::try_collect(
+                ::std::iter::Iterator::map(::std::iter::IntoIterator::into_iter(collect!(@arg $range)), $f)
+            );
+            result
+        }
+    };
+
+    [try_gen $range:tt: $f:expr] => {
+        collect![try_gen $range as ::std::vec::Vec<_>: $f]
+    };
+
+    // Initialise a map from keys to sets of values.
+    [group_set as $col_ty:ty: $($ks:expr => $vs:expr),* $(,)*] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            $(
+                col.entry($ks).or_default().insert($vs);
+            )*
+            col
+        }
+    };
+
+    [group_set: $($es:tt)*] => {
+        collect![group_set as ::std::collections::HashMap<_, ::std::collections::HashSet<_>>: $($es)*]
+    };
+
+    // Initialise a map, refusing to overwrite any entry.
+    [no_overwrite as $col_ty:ty: $($ks:expr => $vs:expr),* $(,)*] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            col.reserve(collect!(@count_tts $(($ks))*));
+            $(
+                $crate::insert_new(&mut col, $ks, $vs);
+            )*
+            col
+        }
+    };
+
+    [no_overwrite: $($es:tt)*] => {
+        collect![no_overwrite as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map, adding together the values of duplicate keys.
+    [sum_map as $col_ty:ty: $($ks:expr => $vs:expr),* $(,)*] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            $(
+                *col.entry($ks).or_default() += $vs;
+            )*
+            col
+        }
+    };
+
+    [sum_map: $($es:tt)*] => {
+        collect![sum_map as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map, counting how many entries overwrote an earlier one.
+    [counted as $col_ty:ty: $k0:expr => $v0:expr $(, $ks:expr => $vs:expr)* $(,)*] => {
+        {
+            // The first entry can't overwrite anything, so it's used to reserve space for the rest.
+            let mut col: $col_ty = ::std::default::Default::default();
+            ::std::iter::Extend::extend(&mut col, $crate::SizeHintIter {
+                item: ::std::option::Option::Some(($k0, $v0)),
+                count: collect!(@count_tts ($k0) $(($ks))*)
+            });
+            let mut overwrites = 0usize;
+            $(
+                if col.insert($ks, $vs).is_some() {
+                    overwrites += 1;
+                }
+            )*
+            (col, overwrites)
+        }
+    };
+
+    // Initialise a `BinaryHeap` by heapifying a `Vec` in one go.
+    [heapify from $src:expr] => {
+        {
+            let vec: ::std::vec::Vec<_> = ::std::iter::FromIterator::from_iter($src);
+            ::std::collections::BinaryHeap::from(vec)
+        }
+    };
+
+    [heapify: $($es:tt)+] => {
+        ::std::collections::BinaryHeap::from(collect![as ::std::vec::Vec<_>: $($es)+])
+    };
+
+    // Initialise a `Vec<char>` from the characters of a string.
+    [chars from $s:expr] => {
+        {
+            let s = $s;
+            let s: &str = ::std::convert::AsRef::as_ref(&s);
+            // A string can't contain more characters than it does bytes.
+            let mut col: ::std::vec::Vec<char> = ::std::vec::Vec::with_capacity(s.len());
+            ::std::iter::Extend::extend(&mut col, s.chars());
+            col
+        }
+    };
+
+    // Initialise a `String` from pieces with their case changed.
+    [upper as $col_ty:ty $(:)*] => {
+        collect![as $col_ty]
+    };
+
+    [upper as $col_ty:ty: $($es:expr),+ $(,)*] => {
+        collect!(@case push_upper, $col_ty, [$($es),+])
+    };
+
+    [upper: $($es:tt)*] => {
+        collect![upper as ::std::string::String: $($es)*]
+    };
+
+    [lower as $col_ty:ty $(:)*] => {
+        collect![as $col_ty]
+    };
+
+    [lower as $col_ty:ty: $($es:expr),+ $(,)*] => {
+        collect!(@case push_lower, $col_ty, [$($es),+])
+    };
+
+    [lower: $($es:tt)*] => {
+        collect![lower as ::std::string::String: $($es)*]
+    };
+
+    // Initialise a `Vec<u8>` by decoding a hexadecimal string.
+    [hex from $s:expr] => {
+        match collect![try_hex from $s] {
+            ::std::result::Result::Ok(v) => v,
+            ::std::result::Result::Err(e) => panic!("collect![hex]: {}", e),
+        }
+    };
+
+    [try_hex from $s:expr] => {
+        {
+            let s = $s;
+            $crate::decode_hex(::std::convert::AsRef::as_ref(&s))
+        }
+    };
+
+    // Initialise a `Vec<u8>` by decoding a string of binary digits.
+    [bits from $s:expr] => {
+        match collect![try_bits from $s] {
+            ::std::result::Result::Ok(v) => v,
+            ::std::result::Result::Err(e) => panic!("collect![bits]: {}", e),
+        }
+    };
+
+    [try_bits from $s:expr] => {
+        {
+            let s = $s;
+            $crate::decode_bits(::std::convert::AsRef::as_ref(&s))
+        }
+    };
+
+    // Initialise a sequence by splicing together several inner sequences.
+    [flatten as $col_ty:ty: $($inner:expr),+ $(,)*] => {
+        collect![as $col_ty: $(..$inner),+]
+    };
+
+    [flatten: $($inner:expr),+ $(,)*] => {
+        collect![flatten as ::std::vec::Vec<_>: $($inner),+]
+    };
+
+    // Initialise a sequence from the contents of a nested one.
+    [flatten_all as $col_ty:ty: $outer:expr] => {
+        {
+            let outer = $outer;
+            let len: usize = outer.iter().map(|inner| inner.len()).sum();
+            let mut col = <$col_ty>::with_capacity(len);
+            for inner in outer {
+                ::std::iter::Extend::extend(&mut col, inner);
+            }
+            col
+        }
+    };
+
+    [flatten_all: $outer:expr] => {
+        collect![flatten_all as ::std::vec::Vec<_>: $outer]
+    };
+
+    // Initialise a `BTreeSet` from the union or intersection of several sets.
+    [btree_union: $($srcs:expr),+ $(,)*] => {
+        {
+            let mut col = ::std::collections::BTreeSet::new();
+            $(
+                ::std::iter::Extend::extend(&mut col, ($srcs).iter().cloned());
+            )+
+            col
+        }
+    };
+
+    [btree_intersection: $first:expr $(, $rest:expr)* $(,)*] => {
+        {
+            let mut col: ::std::collections::BTreeSet<_> = ($first).iter().cloned().collect();
+            $(
+                col = col.intersection(&$rest).cloned().collect();
+            )*
+            col
+        }
+    };
+
+    [btree_merge: $first:expr $(, $rest:expr)* $(,)*] => {
+        {
+            let mut col: ::std::collections::BTreeMap<_, _> = $first;
+            $(
+                col.append(&mut { $rest });
+            )*
+            col
+        }
+    };
+
+    // Initialise a sequence by parsing each element from a string.
+    [parse as $col_ty:ty: $($es:expr),+ $(,)*] => {
+        {
+            let result: ::std::result::Result<$col_ty, _> = $crate::try_collect(
+                ::std::iter::Iterator::map(
+                    ::std::iter::Iterator::enumerate(::std::iter::IntoIterator::into_iter([$($es),+])),
+                    |(index, s)| {
+                        ::std::str::FromStr::from_str(::std::convert::AsRef::<str>::as_ref(&s))
+                            .map_err(|error| $crate::ParseError { index, error })
+                    }
+                )
+            );
+            result
+        }
+    };
+
+    // Initialise a sequence from the fields of a comma-separated string.
+    [csv as $col_ty:ty: $s:expr] => {
+        {
+            let s: &str = $s;
+            let mut col = <$col_ty>::with_capacity(s.matches(',').count() + 1);
+            ::std::iter::Extend::extend(&mut col, ::std::iter::Iterator::map(s.split(','), ::std::primitive::str::trim));
+            col
+        }
+    };
+
+    [csv from $s:expr] => {
+        collect![csv as ::std::vec::Vec<_>: $s]
+    };
+
+    [csv_parse as $col_ty:ty: $s:expr] => {
+        {
+            let s: &str = $s;
+            let result: ::std::result::Result<$col_ty, _> = $crate::try_collect(
+                ::std::iter::Iterator::map(
+                    ::std::iter::Iterator::enumerate(s.split(',')),
+                    |(index, field)| {
+                        ::std::str::FromStr::from_str(field.trim())
+                            .map_err(|error| $crate::ParseError { index, error })
+                    }
+                )
+            );
+            result
+        }
+    };
+
+    // Initialise a map by swapping the keys and values of another.
+    [invert from $src:tt as $col_ty:ty] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            ::std::iter::Extend::extend(
+                &mut col,
+                ::std::iter::Iterator::map(
+                    ::std::iter::IntoIterator::into_iter(collect!(@arg $src)),
+                    |(k, v)| (v, k)
+                )
+            );
+            col
+        }
+    };
+
+    [invert from $src:expr] => {
+        collect![invert from ($src) as ::std::collections::HashMap<_, _>]
+    };
+
+    // Initialise a set from the keys or values of a map.
+    [key_set from $src:tt as $col_ty:ty] => {
+        {
+            let col: $col_ty = ::std::iter::FromIterator::from_iter(collect!(@arg $src).keys().cloned());
+            col
+        }
+    };
+
+    [key_set from $src:expr] => {
+        collect![key_set from ($src) as ::std::collections::HashSet<_>]
+    };
+
+    [value_set from $src:tt as $col_ty:ty] => {
+        {
+            let col: $col_ty = ::std::iter::FromIterator::from_iter(collect!(@arg $src).values().cloned());
+            col
+        }
+    };
+
+    [value_set from $src:expr] => {
+        collect![value_set from ($src) as ::std::collections::HashSet<_>]
+    };
+
+    // Initialise a map from page numbers to consecutive chunks of another sequence.
+    [pages $n:tt from $src:tt as $col_ty:ty] => {
+        {
+            let col: $col_ty = ::std::iter::FromIterator::from_iter($crate::Pages::new(collect!(@arg $src), collect!(@arg $n)));
+            col
+        }
+    };
+
+    [pages $n:tt from $src:expr] => {
+        collect![pages $n from ($src) as ::std::collections::HashMap<_, _>]
+    };
+
+    // Initialise a sequence from the segments of a slice between delimiters.
+    [split_on $delim:tt from $src:tt as $col_ty:ty] => {
+        {
+            let delim = collect!(@arg $delim);
+            let data: &[_] = collect!(@arg $src);
+            let count = ::std::iter::Iterator::count(::std::iter::Iterator::filter(data.iter(), |e| **e == delim));
+            let mut col = <$col_ty>::with_capacity(count + 1);
+            ::std::iter::Extend::extend(&mut col, data.split(|e| *e == delim));
+            col
+        }
+    };
+
+    [split_on $delim:tt from $src:expr] => {
+        collect![split_on $delim from ($src) as ::std::vec::Vec<_>]
+    };
+
+    // Initialise a sequence from the adjacent pairs of another.
+    [windows from $src:tt as $col_ty:ty] => {
+        {
+            let col: $col_ty = ::std::iter::FromIterator::from_iter($crate::Windows::new(collect!(@arg $src)));
+            col
+        }
+    };
+
+    [windows from $src:expr] => {
+        collect![windows from ($src) as ::std::vec::Vec<_>]
+    };
+
+    // Initialise a sequence of a fixed length, filled with a default and then some specific elements.
+    [dense $len:tt default $d:tt: $($is:expr => $vs:expr),* $(,)*] => {
+        {
+            let len: usize = collect!(@arg $len);
+            let mut col = ::std::vec::Vec::with_capacity(len);
+            ::std::iter::Extend::extend(&mut col, ::std::iter::Iterator::take(::std::iter::repeat(collect!(@arg $d)), len));
+            $(
+                {
+                    let i: usize = $is;
+                    assert!(i < len, "collect![dense]: index {} is out of bounds for length {}", i, len);
+                    col[i] = $vs;
+                }
+            )*
+            col
+        }
+    };
+
+    // Initialise a sequence by alternating between the elements of two groups.
+    [interleave as $col_ty:ty: ($($as:expr),* $(,)*), ($($bs:expr),* $(,)*) $(,)*] => {
+        collect!(@interleave $col_ty, [], ($($as),*), ($($bs),*))
+    };
+
+    [interleave: $($es:tt)*] => {
+        collect![interleave as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a sequence of elements paired with their positions.
+    [with_index as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let mut index = 0usize;
+            let col: $col_ty = collect![as $col_ty: $({
+                let e = $es;
+                index += 1;
+                (index - 1, e)
+            }),*];
+            col
+        }
+    };
+
+    [with_index: $($es:tt)*] => {
+        collect![with_index as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a map from each element to its position, counting from one.
+    [rank_map as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let mut rank = 0usize;
+            let col: $col_ty = collect![as $col_ty: $({
+                let e = $es;
+                rank += 1;
+                (e, rank)
+            }),*];
+            col
+        }
+    };
+
+    [rank_map: $($es:tt)*] => {
+        collect![rank_map as ::std::collections::HashMap<_, usize>: $($es)*]
+    };
+
+    // Initialise a sequence from the running state of a scan over another.
+    [scan $init:expr, $f:tt over $src:tt as $col_ty:ty] => {
+        {
+            let iter = ::std::iter::Iterator::scan(::std::iter::IntoIterator::into_iter(collect!(@arg $src)), $init, collect!(@arg $f));
+            // The scan might stop at any point, so only its lower bound is certain.  Reserving for the source's size instead could ask for far more than is ever used (or overflow, for something like `0..u64::MAX`), so leave the growth to `extend`.
+            let mut col = <$col_ty>::with_capacity(::std::iter::Iterator::size_hint(&iter).0);
+            ::std::iter::Extend::extend(&mut col, iter);
+            col
+        }
+    };
+
+    [scan $init:expr, $f:tt over $src:expr] => {
+        collect![scan $init, $f over ($src) as ::std::vec::Vec<_>]
+    };
+
+    // Initialise a sequence from a seed and a function producing each successor.
+    [seq from $init:expr, $f:tt as $col_ty:ty] => {
+        {
+            let col: $col_ty = ::std::iter::FromIterator::from_iter(
+                ::std::iter::successors(::std::option::Option::Some($init), collect!(@arg $f))
+            );
+            col
+        }
+    };
+
+    [seq from $init:expr, $f:expr] => {
+        collect![seq from $init, ($f) as ::std::vec::Vec<_>]
+    };
+
+    // Initialise a sequence with a separator between each pair of elements.
+    [intersperse $sep:tt as $col_ty:ty $(:)*] => {
+        {
+            // There's nothing to separate, but `SEP` is still evaluated, as promised.
+            let _ = collect!(@arg $sep);
+            collect![as $col_ty]
+        }
+    };
+
+    [intersperse $sep:tt as $col_ty:ty: $e0:expr $(, $es:expr)* $(,)*] => {
+        {
+            let sep = collect!(@arg $sep);
+            collect![as $col_ty: $e0 $(, ::std::clone::Clone::clone(&sep), $es)*]
+        }
+    };
+
+    [intersperse $sep:tt: $($es:tt)*] => {
+        collect![intersperse $sep as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a sequence with successive items from an iterator between each pair of elements.
+    [weave $seps:tt as $col_ty:ty $(:)*] => {
+        collect![as $col_ty]
+    };
+
+    [weave $seps:tt as $col_ty:ty: $e0:expr $(, $es:expr)* $(,)*] => {
+        {
+            let mut seps = ::std::iter::Iterator::fuse(::std::iter::IntoIterator::into_iter(collect!(@arg $seps)));
+            let gaps: usize = collect!(@count_tts $(($es))*);
+            // Only reserve for as many separators as are certain to turn up.
+            let (lo, _) = ::std::iter::Iterator::size_hint(&seps);
+            let mut col = <$col_ty>::with_capacity(1 + gaps + ::std::cmp::min(gaps, lo));
+            ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some($e0));
+            $(
+                ::std::iter::Extend::extend(&mut col, ::std::iter::Iterator::next(&mut seps));
+                ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some($es));
+            )*
+            col
+        }
+    };
+
+    [weave $seps:tt: $($es:tt)*] => {
+        collect![weave $seps as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a string by formatting each element with `Display`.
+    [fmt as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            // The formatted length is unknown, so this is a best-effort guess of one byte per element.
+            let mut col = <$col_ty>::with_capacity(collect!(@count_tts $(($es))*));
+            $(
+                collect!(@write_fmt col, "{}", $es);
+            )*
+            col
+        }
+    };
+
+    [fmt: $($es:tt)*] => {
+        collect![fmt as ::std::string::String: $($es)*]
+    };
+
+    [fmt_join $sep:tt as $col_ty:ty: $($es:expr),* $(,)*] => {
+        collect!(@fmt_join $sep, $col_ty, [$($es),*])
+    };
+
+    [fmt_join $sep:tt: $($es:tt)*] => {
+        collect![fmt_join $sep as ::std::string::String: $($es)*]
+    };
+
+    [display_join $sep:tt from $src:tt as $col_ty:ty] => {
+        {
+            let sep = ::std::string::ToString::to_string(&collect!(@arg $sep));
+            let iter = ::std::iter::IntoIterator::into_iter(collect!(@arg $src));
+            // Every item takes at least one byte, and every gap a whole separator.
+            let (lo, _) = ::std::iter::Iterator::size_hint(&iter);
+            let mut col = <$col_ty>::with_capacity(lo + lo.saturating_sub(1) * sep.len());
+            for (i, e) in ::std::iter::Iterator::enumerate(iter) {
+                if i == 0 {
+                    collect!(@write_fmt col, "{}", e);
+                } else {
+                    collect!(@write_fmt col, "{}{}", sep, e);
+                }
+            }
+            col
+        }
+    };
+
+    [display_join $sep:tt from $src:expr] => {
+        collect![display_join $sep from ($src) as ::std::string::String]
+    };
+
+    // Initialise a sequence from only the first few elements, without evaluating the rest.
+    [take $n:tt as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let n: usize = collect!(@arg $n);
+            let n = ::std::cmp::min(n, collect!(@count_tts $(($es))*));
+            let mut col = <$col_ty>::with_capacity(n);
+            let mut taken = 0;
+            $(
+                if taken < n {
+                    taken += 1;
+                    ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some($es));
+                }
+            )*
+            col
+        }
+    };
+
+    [take $n:tt: $($es:tt)*] => {
+        collect![take $n as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a sequence which keeps only the last few elements.
+    [bounded $cap:tt as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let cap: usize = collect!(@arg $cap);
+            let mut col = <$col_ty>::with_capacity(cap);
+            $(
+                {
+                    let e = $es;
+                    if cap > 0 {
+                        // Evict *before* pushing, so the collection never needs to grow.
+                        if col.len() == cap {
+                            col.pop_front();
+                        }
+                        col.push_back(e);
+                    }
+                }
+            )*
+            col
+        }
+    };
+
+    [bounded $cap:tt: $($es:tt)*] => {
+        collect![bounded $cap as ::std::collections::VecDeque<_>: $($es)*]
+    };
+
+    // Initialise a sequence of key-value pairs, sorted by key.
+    [sorted_pairs: $($ks:expr => $vs:expr),* $(,)*] => {
+        collect!(@post (col) [as ::std::vec::Vec<_>: $(($ks, $vs)),*] col.sort_by(|a, b| a.0.cmp(&b.0));)
+    };
+
+    // Initialise a map, wrapping each value in a cell or pointer.
+    [cell_values as $col_ty:ty: $($es:tt)*] => {
+        collect!(@wrap_values ::std::cell::RefCell::new, $col_ty, $($es)*)
+    };
+
+    [cell_values: $($es:tt)*] => {
+        collect![cell_values as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    [mutex_values as $col_ty:ty: $($es:tt)*] => {
+        collect!(@wrap_values ::std::sync::Mutex::new, $col_ty, $($es)*)
+    };
+
+    [mutex_values: $($es:tt)*] => {
+        collect![mutex_values as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    [rc_values as $col_ty:ty: $($es:tt)*] => {
+        collect!(@wrap_values ::std::rc::Rc::new, $col_ty, $($es)*)
+    };
+
+    [rc_values: $($es:tt)*] => {
+        collect![rc_values as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map from each element to a clone of itself.
+    [self_map as $col_ty:ty: $($es:expr),* $(,)*] => {
+        collect![as $col_ty: $({ let k = $es; (::std::clone::Clone::clone(&k), k) }),*]
+    };
+
+    [self_map: $($es:tt)*] => {
+        collect![self_map as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map of maps from two-level keys.
+    [nested as $col_ty:ty: $($k1s:expr, $k2s:expr => $vs:expr),* $(,)*] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            $(
+                col.entry($k1s).or_default().insert($k2s, $vs);
+            )*
+            col
+        }
+    };
+
+    [nested: $($es:tt)*] => {
+        collect![nested as ::std::collections::HashMap<_, ::std::collections::HashMap<_, _>>: $($es)*]
+    };
+
+    // Initialise a map with lowercased string keys.
+    [ci_map as $col_ty:ty: $($ks:expr => $vs:expr),* $(,)*] => {
+        collect![as $col_ty: $(::std::convert::AsRef::<str>::as_ref(&$ks).to_lowercase() => $vs),*]
+    };
+
+    [ci_map: $($es:tt)*] => {
+        collect![ci_map as ::std::collections::HashMap<::std::string::String, _>: $($es)*]
+    };
+
+    // Initialise a map with a common prefix on every string key.
+    [prefix $p:tt map as $col_ty:ty: $($ks:expr => $vs:expr),* $(,)*] => {
+        {
+            let prefix = collect!(@arg $p);
+            let prefix = ::std::convert::AsRef::<str>::as_ref(&prefix);
+            collect![as $col_ty: $(
+                {
+                    let mut key = ::std::string::String::from(prefix);
+                    key.push_str(::std::convert::AsRef::<str>::as_ref(&$ks));
+                    key
+                } => $vs
+            ),*]
+        }
+    };
+
+    [prefix $p:tt map: $($es:tt)*] => {
+        collect![prefix $p map as ::std::collections::HashMap<::std::string::String, _>: $($es)*]
+    };
+
+    // Initialise a map with identifier keys, turned into strings.
+    [obj as $col_ty:ty: $($ks:ident => $vs:expr),* $(,)*] => {
+        collect![as $col_ty: $(stringify!($ks) => $vs),*]
+    };
+
+    [obj: $($es:tt)*] => {
+        collect![obj as ::std::collections::HashMap<&'static str, _>: $($es)*]
+    };
+
+    // Initialise a map from field names and values, written like a struct literal.
+    [fields_of { $($ks:ident: $vs:expr),* $(,)* } as $col_ty:ty] => {
+        collect![obj as $col_ty: $($ks => $vs),*]
+    };
+
+    [fields_of { $($es:tt)* }] => {
+        collect![fields_of { $($es)* } as ::std::collections::BTreeMap<&'static str, _>]
+    };
+
+    // Initialise a map from alternating keys and values.
+    [pairs as $col_ty:ty: $($es:expr),* $(,)*] => {
+        collect!(@pairs $col_ty, [], $($es,)*)
+    };
+
+    [pairs: $($es:tt)*] => {
+        collect![pairs as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map from parallel arrays of keys and values.
+    [zip_arrays $ks:expr, $vs:tt as $col_ty:ty] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            // Zipping two arrays gives an exact size hint, so the map can reserve for the shorter of the two.
+            ::std::iter::Extend::extend(&mut col, ::std::iter::Iterator::zip(
+                ::std::iter::IntoIterator::into_iter($ks),
+                collect!(@arg $vs),
+            ));
+            col
+        }
+    };
+
+    [zip_arrays $ks:expr, $vs:expr] => {
+        collect![zip_arrays $ks, ($vs) as ::std::collections::HashMap<_, _>]
+    };
+
+    // Initialise a map from parallel keys and values, combining the values of repeated keys.
+    [zip_merge $ks:expr, $vs:tt with $f:tt as $col_ty:ty] => {
+        {
+            let mut f = collect!(@arg $f);
+            let mut col: $col_ty = ::std::default::Default::default();
+            let pairs = ::std::iter::Iterator::zip(
+                ::std::iter::IntoIterator::into_iter($ks),
+                collect!(@arg $vs),
+            );
+            for (k, v) in pairs {
+                let v = match col.remove(&k) {
+                    ::std::option::Option::Some(old) => f(old, v),
+                    ::std::option::Option::None => v,
+                };
+                col.insert(k, v);
+            }
+            col
+        }
+    };
+
+    [zip_merge $ks:expr, $vs:tt with $f:tt] => {
+        collect![zip_merge $ks, $vs with $f as ::std::collections::HashMap<_, _>]
+    };
+
+    // Initialise a sequence, checking each element against a predicate.
+    [validate $pred:tt as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let pred = collect!(@arg $pred);
+            let mut col = <$col_ty>::with_capacity(collect!(@count_tts $(($es))*));
+            let mut failed = ::std::option::Option::None;
+            let mut index = 0usize;
+            $(
+                if failed.is_none() {
+                    let e = $es;
+                    if pred(&e) {
+                        ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some(e));
+                    } else {
+                        failed = ::std::option::Option::Some(index);
+                    }
+                    index += 1;
+                }
+            )*
+            match failed {
+                ::std::option::Option::Some(index) => ::std::result::Result::Err($crate::ValidationError { index }),
+                ::std::option::Option::None => ::std::result::Result::Ok(col),
+            }
+        }
+    };
+
+    [validate $pred:tt: $($es:tt)*] => {
+        collect![validate $pred as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a sequence, skipping elements equal to a sentinel.
+    [drop_eq $sentinel:tt as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let sentinel = collect!(@arg $sentinel);
+            // This is only an upper bound, since any number of elements might be dropped.
+            let mut col = <$col_ty>::with_capacity(collect!(@count_tts $(($es))*));
+            $(
+                {
+                    let e = $es;
+                    if e != sentinel {
+                        ::std::iter::Extend::extend(&mut col, ::std::option::Option::Some(e));
+                    }
+                }
+            )*
+            col
+        }
+    };
+
+    [drop_eq $sentinel:tt: $($es:tt)*] => {
+        collect![drop_eq $sentinel as ::std::vec::Vec<_>: $($es)*]
+    };
+
+    // Initialise a map by splitting `KEY=VALUE` strings.
+    [try_env_pairs as $col_ty:ty: $($es:expr),* $(,)*] => {
+        {
+            let result: ::std::result::Result<$col_ty, _> = $crate::try_collect(
+                ::std::iter::Iterator::map(
+                    ::std::iter::Iterator::enumerate(::std::iter::IntoIterator::into_iter([$($es),*])),
+                    |(index, pair)| $crate::split_env_pair(index, pair)
+                )
+            );
+            result
+        }
+    };
+
+    [try_env_pairs: $($es:tt)*] => {
+        collect![try_env_pairs as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    [env_pairs as $col_ty:ty: $($es:tt)*] => {
+        match collect![try_env_pairs as $col_ty: $($es)*] {
+            ::std::result::Result::Ok(col) => col,
+            ::std::result::Result::Err(e) => panic!("collect![env_pairs]: {}", e),
+        }
+    };
+
+    [env_pairs: $($es:tt)*] => {
+        collect![env_pairs as ::std::collections::HashMap<_, _>: $($es)*]
+    };
+
+    // Initialise a map from items, keyed by a field extractor.
+    [index_by $f:tt from $iter:expr] => {
+        {
+            let mut col: ::std::collections::HashMap<_, _> = ::std::default::Default::default();
+            ::std::iter::Extend::extend(
+                &mut col,
+                ::std::iter::Iterator::map($crate::MapKeys::new($iter, collect!(@arg $f)), |(v, k)| (k, v))
+            );
+            col
+        }
+    };
+
+    // Initialise a map of how many items fall into each group.
+    [group_count $f:tt from $iter:tt as $col_ty:ty] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            for (_, k) in $crate::MapKeys::new(collect!(@arg $iter), collect!(@arg $f)) {
+                *col.entry(k).or_insert(0usize) += 1;
+            }
+            col
+        }
+    };
+
+    [group_count $f:tt from $iter:expr] => {
+        collect![group_count $f from ($iter) as ::std::collections::HashMap<_, usize>]
+    };
+
+    // Initialise a map from keys to buckets of the items which produced them.
+    [classify $f:tt from $iter:tt as $col_ty:ty] => {
+        {
+            let mut col: $col_ty = ::std::default::Default::default();
+            for (v, k) in $crate::MapKeys::new(collect!(@arg $iter), collect!(@arg $f)) {
+                ::std::iter::Extend::extend(col.entry(k).or_default(), ::std::option::Option::Some(v));
+            }
+            col
+        }
+    };
+
+    [classify $f:tt from $iter:expr] => {
+        collect![classify $f from ($iter) as ::std::collections::HashMap<_, ::std::vec::Vec<_>>]
+    };
+
+    // Initialise a sequence or map with a fully inferred contained type.
+    [$($es:tt)+] => {
+        collect![as _: $($es)+]
+    };
+}
+
+/**
+This macro pushes one or more values onto the `Vec`s stored in a map, creating any missing `Vec`s with `Default`.  It can be used to incrementally build a `HashMap<K, Vec<V>>` (or any other map with an `entry` API), for example inside a loop.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+# fn main() {
+let mut m: HashMap<&str, Vec<i32>> = collect![];
+for i in 0..4 {
+    push_into_map!(m; if i % 2 == 0 { "even" } else { "odd" } => i);
+}
+push_into_map!(m; "odd" => 5, "even" => 6);
+assert_eq!(m["even"], [0, 2, 6]);
+assert_eq!(m["odd"], [1, 3, 5]);
+# }
+```
+*/
+#[macro_export]
+macro_rules! push_into_map {
+    ($map:expr; $($ks:expr => $vs:expr),+ $(,)*) => {
+        {
+            let map = &mut $map;
+            $(
+                map.entry($ks).or_default().push($vs);
+            )+
+        }
+    };
+}
+
+/**
+This macro builds a `HashMap` from `key => value` pairs, returning an error if any key is given more than once.  This is useful for validating user-provided configuration, where a repeated key is more likely to be a mistake than an intentional override.
+
+The result is a `Result<HashMap<K, V>, DuplicateKeyError<K>>`.  Entries are evaluated from left to right, stopping at the first duplicate key; neither its value nor any subsequent entries are evaluated.
+
+```
+# #[macro_use] extern crate collect_mac;
+# fn main() {
+let ok = try_collect_map!["a" => 1, "b" => 2];
+assert_eq!(ok.unwrap().len(), 2);
+
+let err = try_collect_map!["a" => 1, "b" => 2, "a" => 3];
+assert_eq!(err.unwrap_err().key, "a");
+# }
+```
+*/
+#[macro_export]
+macro_rules! try_collect_map {
+    ($($ks:expr => $vs:expr),+ $(,)*) => {
+        {
+            let mut map = ::std::collections::HashMap::with_capacity(collect!(@count_tts $(($ks))+));
+            let mut dup = ::std::option::Option::None;
+            $(
+                if dup.is_none() {
+                    let k = $ks;
+                    if map.contains_key(&k) {
+                        dup = ::std::option::Option::Some(k);
+                    } else {
+                        map.insert(k, $vs);
+                    }
+                }
+            )+
+            match dup {
+                ::std::option::Option::None => ::std::result::Result::Ok(map),
+                ::std::option::Option::Some(key) => ::std::result::Result::Err($crate::DuplicateKeyError { key }),
+            }
+        }
+    };
+}
+
+/**
+This macro builds a `HashSet` from a sequence of values, and also returns a `Vec` of every value which was a duplicate of one already in the set, in the order they were encountered.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashSet;
+# fn main() {
+let (set, dups) = collect_set_dups![1, 2, 2, 3, 1];
+assert_eq!(set, collect![as HashSet<_>: 1, 2, 3]);
+assert_eq!(dups, [2, 1]);
+# }
+```
+*/
+#[macro_export]
+macro_rules! collect_set_dups {
+    ($($es:expr),* $(,)*) => {
+        {
+            let mut set = ::std::collections::HashSet::with_capacity(collect!(@count_tts $(($es))*));
+            let mut dups = ::std::vec::Vec::new();
+            $(
+                let v = $es;
+                if set.contains(&v) {
+                    dups.push(v);
+                } else {
+                    set.insert(v);
+                }
+            )*
+            (set, dups)
+        }
+    };
+}
+
+/**
+This macro defines a `static` collection which is built with `collect!` the first time it is used, by wrapping it in a `once_cell::sync::Lazy`.  It is only available with the `once_cell` feature enabled.
+
+The contents between the braces are passed to `collect!` as-is, after `as TYPE:`, so anything `collect!` accepts there will work.  Attributes and a visibility can be given before the name.  The collection must be `Sync`, as with any other `static`.
+
+```
+# #[macro_use] extern crate collect_mac;
+# use std::collections::HashMap;
+static_map!(pub(crate) CODES: HashMap<&'static str, u16> = { "ok" => 200, "not found" => 404 });
+# fn main() {
+assert_eq!(CODES["not found"], 404);
+# }
+```
+*/
+#[cfg(feature = "once_cell")]
+#[macro_export]
+macro_rules! static_map {
+    ($(#[$attrs:meta])* $vis:vis $name:ident: $col_ty:ty = { $($es:tt)* }) => {
+        $(#[$attrs])*
+        $vis static $name: $crate::__once_cell::sync::Lazy<$col_ty> =
+            $crate::__once_cell::sync::Lazy::new(|| collect![as $col_ty: $($es)*]);
+    };
+}
+
+/**
+This iterator's whole purpose in life is to lie whenever it's asked how many items it has.
+
+This is necessary because of how `Extend` is implemented for `Vec`: specifically, it asks for the first element *before* it checks `size_hint`.  As a result, the old trick (of having an empty iterator that reported a false size hint) doesn't work.
+*/
+#[doc(hidden)]
+pub struct SizeHintIter<T> {
+    pub item: Option<T>,
+    pub count: usize,
+}
+
+impl<T> Iterator for SizeHintIter<T> {
+    type Item = T;
 
-Single-allocation construction is tested and guaranteed for the following standard containers:
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        match self.item.take() {
+            Some(v) => {
+                self.count -= 1;
+                Some(v)
+            },
+            None => None
+        }
+    }
 
-* [`HashMap`](http://doc.rust-lang.org/std/collections/struct.HashMap.html)
-* [`HashSet`](http://doc.rust-lang.org/std/collections/struct.HashSet.html)
-* [`String`](http://doc.rust-lang.org/std/string/struct.String.html)
-* [`Vec`](http://doc.rust-lang.org/std/vec/struct.Vec.html)
-* [`VecDeque`](http://doc.rust-lang.org/std/collections/struct.VecDeque.html)
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count, Some(self.count))
+    }
+}
 
-In general, single-allocation construction is done by providing the number of elements through the [`Iterator::size_hint`][Iterator::size_hint] of the *first* call to `Extend`.  The expectation is that the collection will, if possible, pre-allocate enough space for all the elements when it goes to insert the first.
+/**
+Builds a collection from the contents of an array.  This is used by `collect![outline ...]` to keep the insertion code out of the caller.
 
-As an example, here is a simplified version of the `Extend` implementation for `Vec`:
+Arrays have an exactly-sized iterator, so this still only allocates once for collections which respect size hints.
+*/
+#[doc(hidden)]
+#[inline(never)]
+pub fn build_from_array<C, T, const N: usize>(arr: [T; N]) -> C
+where C: Default + Extend<T> {
+    let mut col = C::default();
+    col.extend(arr);
+    col
+}
 
-```ignore
-impl<T> Extend<T> for Vec<T> {
-    #[inline]
-    fn extend<I: IntoIterator<Item=T>>(&mut self, iterable: I) {
-        let mut iterator = iterable.into_iter();
-        while let Some(element) = iterator.next() {
-            let len = self.len();
-            if len == self.capacity() {
-                let (lower, _) = iterator.size_hint();
-                self.reserve(lower.saturating_add(1));
+/**
+Creates an empty collection of the same type as `col`.  This is used by `collect![assert_single_alloc ...]`, so the collection type needn't be written out in full.
+*/
+#[doc(hidden)]
+pub fn empty_like<C: Default>(_col: &C) -> C {
+    C::default()
+}
+
+/**
+Removes every element of `col` whose key has already been seen, keeping the first element for each key.  This is used by `collect![dedup_by_key ...]`, and mostly exists to give the key function's argument a known type.
+*/
+#[doc(hidden)]
+pub fn dedup_by_key<T, K, F>(col: &mut Vec<T>, mut f: F)
+where
+    K: Eq + Hash,
+    F: FnMut(&T) -> K,
+{
+    let mut seen = HashSet::with_capacity(col.len());
+    col.retain(|e| seen.insert(f(e)));
+}
+
+/**
+Passes `col` through if `f` accepts it.  This is used by `collect![try_check ...]`, and gives the predicate's argument a known type.
+*/
+#[doc(hidden)]
+pub fn check<C, F>(col: C, f: F) -> Result<C, CheckError>
+where F: FnOnce(&C) -> bool {
+    if f(&col) {
+        Ok(col)
+    } else {
+        Err(CheckError)
+    }
+}
+
+/**
+Inserts an entry into `map`, panicking if the key is already present.  This is used by `collect![no_overwrite ...]`.
+*/
+#[doc(hidden)]
+pub fn insert_new<K, V, S>(map: &mut HashMap<K, V, S>, key: K, value: V)
+where
+    K: Eq + Hash + fmt::Debug,
+    S: BuildHasher,
+{
+    #[cfg(feature = "nightly")]
+    {
+        if let Err(e) = map.try_insert(key, value) {
+            panic!("collect![no_overwrite]: {}", DuplicateKeyError { key: e.entry.key() });
+        }
+    }
+
+    #[cfg(not(feature = "nightly"))]
+    {
+        use std::collections::hash_map::Entry;
+        match map.entry(key) {
+            Entry::Occupied(e) => panic!("collect![no_overwrite]: {}", DuplicateKeyError { key: e.key() }),
+            Entry::Vacant(e) => {
+                e.insert(value);
             }
-            self.push(element);
         }
     }
 }
-```
 
-[Default]: http://doc.rust-lang.org/std/default/trait.Default.html
-[Extend]: http://doc.rust-lang.org/std/iter/trait.Extend.html
-[Iterator::size_hint]: http://doc.rust-lang.org/std/iter/trait.Iterator.html#method.size_hint
+/**
+Collects an iterator of `Result`s, stopping at the first error.
+
+Like the standard `FromIterator` implementation for `Result`, the lower bound of the size hint is zero, since any item might be an error; the upper bound is passed through from the underlying iterator.  This means an unbounded source (such as `0..`) is fine, so long as an error turns up eventually.
 */
+#[doc(hidden)]
+pub fn try_collect<C, T, E, I>(iter: I) -> Result<C, E>
+where
+    I: IntoIterator<Item=Result<T, E>>,
+    C: FromIterator<T>,
+{
+    let mut err = None;
+    let col = C::from_iter(TryShunt {
+        iter: iter.into_iter(),
+        err: &mut err,
+    });
+    match err {
+        None => Ok(col),
+        Some(e) => Err(e),
+    }
+}
 
 /**
-This macro can be used to easily construct arbitrary collections, including `Vec`, `String`, and `HashMap`.  It also endeavours to construct the collection with a single allocation, where possible.
+Yields the `Ok` values of an iterator of `Result`s, stashing the first `Err` and stopping there.
+*/
+struct TryShunt<'a, I, E: 'a> {
+    iter: I,
+    err: &'a mut Option<E>,
+}
 
-For more details, see [the crate documentation](./index.html).
+impl<'a, I, T, E> Iterator for TryShunt<'a, I, E>
+where I: Iterator<Item=Result<T, E>> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<T> {
+        if self.err.is_some() {
+            return None;
+        }
+        match self.iter.next() {
+            Some(Ok(v)) => Some(v),
+            Some(Err(e)) => {
+                *self.err = Some(e);
+                None
+            },
+            None => None
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.err.is_some() {
+            (0, Some(0))
+        } else {
+            // Any item could be an error, so there's no telling how many will get through.
+            let (_, hi) = self.iter.size_hint();
+            (0, hi)
+        }
+    }
+}
+
+/**
+This iterator pairs each key from an underlying iterator with a value computed from a reference to that key.
+
+Taking the key by reference means the closure doesn't need the key type to be `Clone`.  It also needs to be a named type (rather than a closure inside the macro expansion) so that the compiler infers a signature for the user's closure that works for *any* borrow of the key.
 */
-#[macro_export]
-macro_rules! collect {
-    /*
-    Internal rules.
-    */
+#[doc(hidden)]
+pub struct MapKeys<I, F> {
+    iter: I,
+    f: F,
+}
 
-    (@count_tts $($tts:tt)*) => {
-        0usize $(+ collect!(@replace_expr $tts 1usize))*
-    };
+impl<I, F> MapKeys<I, F> {
+    #[inline]
+    pub fn new<II, V>(keys: II, f: F) -> MapKeys<I, F>
+    where
+        II: IntoIterator<IntoIter=I, Item=I::Item>,
+        I: Iterator,
+        F: FnMut(&I::Item) -> V,
+    {
+        MapKeys {
+            iter: keys.into_iter(),
+            f,
+        }
+    }
+}
 
-    (@replace_expr $_tt:tt $sub:expr) => {
-        $sub
-    };
+impl<I, F, V> Iterator for MapKeys<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item) -> V,
+{
+    type Item = (I::Item, V);
 
-    (@collect
-        ty: $col_ty:ty,
-        es: [$v0:expr, $($vs:expr),* $(,)*],
-        // `cb` is an expression that is inserted after each "step" in constructing the collection.  It largely exists for testing purposes.
-        cb: ($col:ident) $cb:expr,
-    ) => {
-        {
-            const NUM_ELEMS: usize = collect!(@count_tts ($v0) $(($vs))*);
+    #[inline]
+    fn next(&mut self) -> Option<(I::Item, V)> {
+        match self.iter.next() {
+            Some(k) => {
+                let v = (self.f)(&k);
+                Some((k, v))
+            },
+            None => None
+        }
+    }
 
-            let mut $col: $col_ty = ::std::default::Default::default();
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
 
-            $cb;
+/**
+This iterator yields each pair of adjacent items from an underlying iterator, cloning every item except the first and last.
+*/
+#[doc(hidden)]
+pub struct Windows<I: Iterator> {
+    iter: I,
+    prev: Option<I::Item>,
+    started: bool,
+}
 
-            let hint = $crate::SizeHintIter {
-                item: Some($v0),
-                count: NUM_ELEMS
-            };
-            ::std::iter::Extend::extend(&mut $col, hint);
+impl<I: Iterator> Windows<I> {
+    #[inline]
+    pub fn new<II>(src: II) -> Windows<I>
+    where II: IntoIterator<IntoIter=I, Item=I::Item> {
+        Windows {
+            iter: src.into_iter(),
+            prev: None,
+            started: false,
+        }
+    }
+}
 
-            $cb;
+impl<I> Iterator for Windows<I>
+where
+    I: Iterator,
+    I::Item: Clone,
+{
+    type Item = (I::Item, I::Item);
 
-            $(
-                ::std::iter::Extend::extend(&mut $col, Some($vs).into_iter());
-                $cb;
-            )*
+    #[inline]
+    fn next(&mut self) -> Option<(I::Item, I::Item)> {
+        if !self.started {
+            self.started = true;
+            self.prev = self.iter.next();
+        }
+        let prev = self.prev.take()?;
+        let next = self.iter.next()?;
+        self.prev = Some(next.clone());
+        Some((prev, next))
+    }
 
-            $col
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        if self.started {
+            if self.prev.is_some() { (lo, hi) } else { (0, Some(0)) }
+        } else {
+            (lo.saturating_sub(1), hi.map(|hi| hi.saturating_sub(1)))
         }
-    };
+    }
+}
 
-    /*
-    Public rules.
-    */
+/**
+Collections which can be created with a custom allocator.  This is used by `collect![in ALLOC ...]`.
+*/
+#[cfg(feature = "nightly")]
+#[doc(hidden)]
+pub trait WithCapacityIn<A: std::alloc::Allocator> {
+    fn with_capacity_in(capacity: usize, alloc: A) -> Self;
+}
 
-    // Short-hands for initialising an empty collection.
-    [] => {
-        collect![as _:]
-    };
+#[cfg(feature = "nightly")]
+impl<T, A: std::alloc::Allocator> WithCapacityIn<A> for Vec<T, A> {
+    #[inline]
+    fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        Vec::with_capacity_in(capacity, alloc)
+    }
+}
 
-    [as $col_ty:ty] => {
-        collect![as $col_ty:]
-    };
+#[cfg(feature = "nightly")]
+impl<T, A: std::alloc::Allocator> WithCapacityIn<A> for std::collections::VecDeque<T, A> {
+    #[inline]
+    fn with_capacity_in(capacity: usize, alloc: A) -> Self {
+        std::collections::VecDeque::with_capacity_in(capacity, alloc)
+    }
+}
 
-    [as $col_ty:ty:] => {
-        {
-            let col: $col_ty = ::std::default::Default::default();
-            col
-        }
-    };
+/**
+Collections which can be reversed.  This is used by `collect![rev ...]`.
+*/
+#[doc(hidden)]
+pub trait Reverse {
+    fn reverse(&mut self);
+}
 
-    // Initialise a sequence with a constrained container type.
-    [as $col_ty:ty: $v0:expr] => { collect![as $col_ty: $v0,] };
+impl<T> Reverse for Vec<T> {
+    #[inline]
+    fn reverse(&mut self) {
+        self[..].reverse();
+    }
+}
 
-    [as $col_ty:ty: $v0:expr, $($vs:expr),* $(,)*] => {
-        collect!(
-            @collect
-            ty: $col_ty,
-            es: [$v0, $($vs),*],
-            cb: (col) (),
-        )
-    };
+impl<T> Reverse for std::collections::VecDeque<T> {
+    #[inline]
+    fn reverse(&mut self) {
+        self.make_contiguous().reverse();
+    }
+}
 
-    // Initialise a map with a constrained container type.
-    [as $col_ty:ty: $($ks:expr => $vs:expr),+ $(,)*] => {
-        // Maps implement FromIterator by taking tuples, so we just need to rewrite each `a:b` as `(a,b)`.
-        collect![as $col_ty: $(($ks, $vs)),+]
-    };
+impl Reverse for String {
+    fn reverse(&mut self) {
+        let mut s = String::with_capacity(self.len());
+        s.extend(self.chars().rev());
+        *self = s;
+    }
+}
 
-    // Initialise a sequence with a fully inferred contained type.
-    [$($vs:expr),+ $(,)*] => {
-        collect![as _: $($vs),+]
-    };
+/**
+Pieces of text which can be appended to a `String` with their case changed.  This is used by `collect![upper ...]` and `collect![lower ...]`.
+*/
+#[doc(hidden)]
+pub trait CasePiece {
+    fn byte_len(&self) -> usize;
+    fn push_upper(self, s: &mut String);
+    fn push_lower(self, s: &mut String);
+}
 
-    // Initialise a map with a fully inferred contained type.
-    [$($ks:expr => $vs:expr),+ $(,)*] => {
-        collect![as _: $($ks => $vs),+]
-    };
+impl CasePiece for &str {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn push_upper(self, s: &mut String) {
+        s.push_str(&self.to_uppercase());
+    }
+
+    // `str::to_lowercase` knows about context-sensitive cases, such as a final sigma, which converting one `char` at a time would miss.
+    fn push_lower(self, s: &mut String) {
+        s.push_str(&self.to_lowercase());
+    }
+}
+
+impl CasePiece for String {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn push_upper(self, s: &mut String) {
+        CasePiece::push_upper(&*self, s);
+    }
+
+    fn push_lower(self, s: &mut String) {
+        CasePiece::push_lower(&*self, s);
+    }
+}
+
+impl CasePiece for char {
+    fn byte_len(&self) -> usize {
+        self.len_utf8()
+    }
+
+    fn push_upper(self, s: &mut String) {
+        s.extend(self.to_uppercase());
+    }
+
+    fn push_lower(self, s: &mut String) {
+        s.extend(self.to_lowercase());
+    }
 }
 
 /**
-This iterator's whole purpose in life is to lie whenever it's asked how many items it has.
+This iterator splits an underlying iterator into numbered chunks of a fixed size, reporting how many chunks are on the way.
+*/
+#[doc(hidden)]
+pub struct Pages<I> {
+    iter: I,
+    size: usize,
+    index: usize,
+}
 
-This is necessary because of how `Extend` is implemented for `Vec`: specifically, it asks for the first element *before* it checks `size_hint`.  As a result, the old trick (of having an empty iterator that reported a false size hint) doesn't work.
+impl<I: Iterator> Pages<I> {
+    #[inline]
+    pub fn new<II>(src: II, size: usize) -> Pages<I>
+    where II: IntoIterator<IntoIter=I, Item=I::Item> {
+        assert!(size != 0, "collect![pages]: page size must not be zero");
+        Pages {
+            iter: src.into_iter(),
+            size,
+            index: 0,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Pages<I> {
+    type Item = (usize, Vec<I::Item>);
+
+    fn next(&mut self) -> Option<(usize, Vec<I::Item>)> {
+        let first = self.iter.next()?;
+        let mut page = Vec::with_capacity(self.size);
+        page.push(first);
+        page.extend(self.iter.by_ref().take(self.size - 1));
+        self.index += 1;
+        Some((self.index - 1, page))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lo, hi) = self.iter.size_hint();
+        (lo.div_ceil(self.size), hi.map(|hi| hi.div_ceil(self.size)))
+    }
+}
+
+/**
+This wraps a collection, hashing every item passed to `Extend` on its way in.  This is used by `collect![with_hash ...]`.
 */
 #[doc(hidden)]
-pub struct SizeHintIter<T> {
-    pub item: Option<T>,
-    pub count: usize,
+pub struct HashOnInsert<C> {
+    col: C,
+    hasher: DefaultHasher,
 }
 
-impl<T> Iterator for SizeHintIter<T> {
-    type Item = T;
+impl<C> HashOnInsert<C> {
+    #[inline]
+    pub fn finish(self) -> (C, u64) {
+        let hash = self.hasher.finish();
+        (self.col, hash)
+    }
+}
 
+impl<C: Default> Default for HashOnInsert<C> {
     #[inline]
-    fn next(&mut self) -> Option<T> {
-        match self.item.take() {
-            Some(v) => {
-                self.count -= 1;
-                Some(v)
-            },
-            None => None
+    fn default() -> HashOnInsert<C> {
+        HashOnInsert {
+            col: C::default(),
+            hasher: DefaultHasher::new(),
         }
     }
+}
 
+impl<C, T> Extend<T> for HashOnInsert<C>
+where C: Extend<T>, T: Hash {
     #[inline]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        (self.count, Some(self.count))
+    fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
+        let hasher = &mut self.hasher;
+        // `inspect` passes the size hint through, so the collection can still reserve space up front.
+        self.col.extend(iter.into_iter().inspect(|item| item.hash(hasher)));
     }
 }
@@ -0,0 +1,153 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+Runtime parsing support for the forms of `collect!` which build collections out of string literals.
+*/
+use std::error::Error;
+use std::fmt;
+
+/**
+The error produced when `collect![try_hex ...]` is given a string which isn't valid hexadecimal.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HexError {
+    /// The string contained an odd number of digits; the payload is the length of the string.
+    OddLength(usize),
+    /// The string contained a character which isn't a hexadecimal digit.
+    InvalidChar {
+        /// The byte offset of the character within the string.
+        index: usize,
+        /// The offending character.
+        ch: char,
+    },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HexError::OddLength(len) => write!(fmt, "odd number of hex digits ({})", len),
+            HexError::InvalidChar { index, ch } => write!(fmt, "invalid hex digit {:?} at index {}", ch, index),
+        }
+    }
+}
+
+impl Error for HexError {}
+
+/**
+Decodes a string of hexadecimal digit pairs into bytes.
+*/
+#[doc(hidden)]
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, HexError> {
+    fn digit(s: &str, index: usize) -> Result<u8, HexError> {
+        let b = s.as_bytes()[index];
+        match (b as char).to_digit(16) {
+            Some(d) => Ok(d as u8),
+            None => Err(HexError::InvalidChar {
+                index,
+                ch: s[index..].chars().next().unwrap_or(b as char),
+            }),
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(s.len() / 2);
+    let mut i = 0;
+    while i + 1 < s.len() {
+        let hi = digit(s, i)?;
+        let lo = digit(s, i + 1)?;
+        bytes.push(hi << 4 | lo);
+        i += 2;
+    }
+    if i < s.len() {
+        // Report a bad trailing character in preference to the length.
+        digit(s, i)?;
+        return Err(HexError::OddLength(s.len()));
+    }
+    Ok(bytes)
+}
+
+/**
+The error produced when `collect![try_bits ...]` is given a string containing something other than `0` and `1`.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitsError {
+    /// The byte offset of the offending character within the string.
+    pub index: usize,
+    /// The offending character.
+    pub ch: char,
+}
+
+impl fmt::Display for BitsError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "invalid bit {:?} at index {}", self.ch, self.index)
+    }
+}
+
+impl Error for BitsError {}
+
+/**
+Decodes a string of binary digits into bytes, most significant bit first.  A final partial byte is padded with zeros on the right.
+*/
+#[doc(hidden)]
+pub fn decode_bits(s: &str) -> Result<Vec<u8>, BitsError> {
+    let mut bytes = Vec::with_capacity(s.len().div_ceil(8));
+    let mut byte = 0u8;
+    let mut count = 0;
+    for (index, ch) in s.char_indices() {
+        let bit = match ch {
+            '0' => 0,
+            '1' => 1,
+            _ => return Err(BitsError { index, ch }),
+        };
+        byte = byte << 1 | bit;
+        count += 1;
+        if count == 8 {
+            bytes.push(byte);
+            byte = 0;
+            count = 0;
+        }
+    }
+    if count > 0 {
+        bytes.push(byte << (8 - count));
+    }
+    Ok(bytes)
+}
+
+/**
+The error produced when `collect![try_env_pairs ...]` is given an element which doesn't contain an `=`.
+*/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EnvPairError {
+    /// The position of the offending element, counting from zero.
+    pub index: usize,
+    /// The offending element.
+    pub pair: String,
+}
+
+impl fmt::Display for EnvPairError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "element {} ({:?}) is missing an `=`", self.index, self.pair)
+    }
+}
+
+impl Error for EnvPairError {}
+
+/**
+Splits a `KEY=VALUE` string at the first `=`.
+*/
+#[doc(hidden)]
+pub fn split_env_pair(index: usize, pair: &str) -> Result<(&str, &str), EnvPairError> {
+    match pair.find('=') {
+        Some(i) => Ok((&pair[..i], &pair[i + 1..])),
+        None => Err(EnvPairError {
+            index,
+            pair: pair.to_owned(),
+        }),
+    }
+}
@@ -0,0 +1,125 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that forms which promise a single allocation actually deliver one, by counting the allocations made on the current thread.
+*/
+
+#[macro_use] extern crate collect_mac;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCS.try_with(|n| n.set(n.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// Counts the allocations and reallocations made on this thread while running `f`.
+fn count_allocs<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+    let before = ALLOCS.with(|n| n.get());
+    let r = f();
+    let after = ALLOCS.with(|n| n.get());
+    (r, after - before)
+}
+
+#[test]
+fn test_boxed_str() {
+    let owned = String::from("de");
+    let (s, allocs) = count_allocs(|| collect![as Box<str>: "a", "bc", &owned]);
+    assert_eq!(&*s, "abcde");
+    assert_eq!(allocs, 1);
+
+    let (s, allocs) = count_allocs(|| collect![as Box<str>]);
+    assert_eq!(&*s, "");
+    assert_eq!(allocs, 0);
+}
+
+#[test]
+fn test_literal_growth() {
+    let (v, allocs) = count_allocs(|| collect![as Vec<i32>: 0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+    assert_eq!(v, (0..17).collect::<Vec<_>>());
+    assert_eq!(allocs, 1);
+
+    let (d, allocs) = count_allocs(|| collect![as VecDeque<char>: 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i']);
+    assert_eq!(d.into_iter().collect::<String>(), "abcdefghi");
+    assert_eq!(allocs, 1);
+}
+
+#[test]
+fn test_terminate_with() {
+    let (v, allocs) = count_allocs(|| collect![terminate_with 0 as Vec<_>: 1, 2, 3, 4, 5, 6, 7, 8]);
+    assert_eq!(v, [1, 2, 3, 4, 5, 6, 7, 8, 0]);
+    assert_eq!(allocs, 1);
+
+    let (v, allocs) = count_allocs(|| collect![terminate_with 0 as Vec<i32>:]);
+    assert_eq!(v, [0]);
+    assert_eq!(allocs, 1);
+}
+
+#[test]
+fn test_assert_single_alloc() {
+    // In debug builds, the check allocates one more collection to compare capacities with.
+    let checks = if cfg!(debug_assertions) { 1 } else { 0 };
+
+    let (v, allocs) = count_allocs(|| collect![assert_single_alloc as Vec<_>: 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(v, [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    assert_eq!(allocs, 1 + checks);
+
+    let (d, allocs) = count_allocs(|| collect![assert_single_alloc as VecDeque<_>: 'a', 'b', 'c',]);
+    assert_eq!(d, ['a', 'b', 'c']);
+    assert_eq!(allocs, 1 + checks);
+
+    let (m, allocs) = count_allocs(|| collect![assert_single_alloc as HashMap<i32, i32>: 1 => 2, 3 => 4]);
+    assert_eq!(m, collect![1 => 2, 3 => 4]);
+    assert_eq!(allocs, 1 + checks);
+
+    let m = collect![assert_single_alloc as HashMap<i32, i32>: 5 => 6];
+    assert_eq!(m[&5], 6);
+
+    let v = collect![assert_single_alloc as Vec<i32>];
+    assert!(v.is_empty());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "collect![assert_single_alloc]: capacity went from 0 to 20, but a single allocation for 2 elements gives 8")]
+fn test_assert_single_alloc_fails() {
+    // `String` only reserves space for each piece as it arrives.
+    let _ = collect![assert_single_alloc as String: "0123456789", "abcdef"];
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "collect![assert_single_alloc]: capacity went from 0 to 16, but a single allocation for 8 elements gives 8")]
+fn test_assert_single_alloc_fails_multibyte() {
+    // `String` reserves one byte per `char`, so two-byte characters make it grow again.
+    let _ = collect![assert_single_alloc as String: 'ä', 'ä', 'ä', 'ä', 'ä', 'ä', 'ä', 'ä'];
+}
@@ -0,0 +1,75 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that `collect![into ...]` can fill existing collections, including ones chosen at runtime through a `CollectBuilder`.
+*/
+#[macro_use] extern crate collect_mac;
+
+use collect_mac::CollectBuilder;
+use std::collections::{HashMap, HashSet};
+
+#[test]
+fn test_into() {
+    let mut v = vec![1];
+    collect![into v; 2, 3,];
+    assert_eq!(v, [1, 2, 3]);
+
+    let mut m: HashMap<&str, i32> = collect![];
+    collect![into m; "a" => 1, "b" => 2];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 1, "b" => 2]);
+
+    let mut s = String::from("ab");
+    collect![into s; 'c'];
+    assert_eq!(s, "abc");
+}
+
+#[test]
+fn test_builder_vec() {
+    let mut v = vec![];
+    {
+        let mut builder = CollectBuilder::new(&mut v);
+        collect![into builder; 1, 2, 3];
+        collect![into builder; 4];
+    }
+    assert_eq!(v, [1, 2, 3, 4]);
+}
+
+#[test]
+fn test_builder_hash_set() {
+    let mut s = HashSet::new();
+    {
+        let mut builder = CollectBuilder::new(&mut s);
+        collect![into builder; "a", "b", "a"];
+        assert!(format!("{:?}", builder).starts_with("CollectBuilder"));
+    }
+    assert_eq!(s, collect![as HashSet<_>: "a", "b"]);
+    // The size hint makes it through the type erasure.
+    assert!(s.capacity() >= 3);
+}
+
+#[test]
+fn test_builder_runtime_choice() {
+    fn fill(builder: &mut CollectBuilder<i32>) {
+        collect![into *builder; 3, 1, 3];
+    }
+
+    let mut v = vec![];
+    let mut s = HashSet::new();
+    for &unique in &[false, true] {
+        let mut builder = if unique {
+            CollectBuilder::new(&mut s)
+        } else {
+            CollectBuilder::new(&mut v)
+        };
+        fill(&mut builder);
+    }
+    assert_eq!(v, [3, 1, 3]);
+    assert_eq!(s.len(), 2);
+}
@@ -0,0 +1,1096 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that the specialised, keyword-introduced forms of `collect!` work as advertised.
+*/
+
+#[macro_use] extern crate collect_mac;
+
+use collect_mac::{BitsError, EnvPairError, HexError, ValidationError};
+
+use std::collections::{BinaryHeap, BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+#[test]
+fn test_range_map() {
+    let m = collect![range_map 0..5 => |k| k*k];
+    let m: BTreeMap<i32, i32> = m;
+    assert_eq!(m, collect![0 => 0, 1 => 1, 2 => 4, 3 => 9, 4 => 16]);
+
+    let m = collect![range_map as HashMap<_, _>: vec!["a", "bb", "ccc"] => |k| k.len()];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 1, "bb" => 2, "ccc" => 3]);
+
+    let m: BTreeMap<i32, i32> = collect![range_map 0..0 => |&k| k];
+    assert!(m.is_empty());
+}
+
+#[test]
+fn test_index_by() {
+    #[derive(Debug, PartialEq)]
+    struct Person {
+        id: u32,
+        name: &'static str,
+    }
+
+    let people = vec![
+        Person { id: 7, name: "Alice" },
+        Person { id: 3, name: "Bob" },
+        Person { id: 7, name: "Carol" },
+    ];
+    let by_id = collect![index_by (|p: &Person| p.id) from people];
+    let by_id: HashMap<u32, Person> = by_id;
+
+    // Duplicate keys: last one wins.
+    assert_eq!(by_id.len(), 2);
+    assert_eq!(by_id[&7], Person { id: 7, name: "Carol" });
+    assert_eq!(by_id[&3], Person { id: 3, name: "Bob" });
+
+    let by_len = collect![index_by (|s: &&str| s.len()) from vec!["a", "bb"]];
+    assert_eq!(by_len, collect![as HashMap<_, _>: 1 => "a", 2 => "bb"]);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Category { Fruit, Vegetable, Grain }
+
+struct Produce {
+    name: &'static str,
+    category: Category,
+}
+
+#[test]
+fn test_group_count() {
+    let items = vec![
+        Produce { name: "apple", category: Category::Fruit },
+        Produce { name: "carrot", category: Category::Vegetable },
+        Produce { name: "pear", category: Category::Fruit },
+        Produce { name: "leek", category: Category::Vegetable },
+        Produce { name: "plum", category: Category::Fruit },
+    ];
+    let counts = collect![group_count (|x| x.category) from &items];
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&Category::Fruit], 3);
+    assert_eq!(counts[&Category::Vegetable], 2);
+    assert!(!counts.contains_key(&Category::Grain));
+
+    let by_len = collect![group_count (|x: &&Produce| x.name.len()) from (&items) as BTreeMap<_, _>];
+    assert_eq!(by_len.into_iter().collect::<Vec<_>>(), vec![(4, 3), (5, 1), (6, 1)]);
+
+    let none = collect![group_count (|x: &i32| x % 2) from Vec::<i32>::new()];
+    assert!(none.is_empty());
+}
+
+#[test]
+fn test_classify() {
+    let numbers = vec![3, 8, 5, 2, 7, 4];
+    let buckets = collect![classify (|x| if x % 2 == 0 { "even" } else { "odd" }) from numbers];
+    assert_eq!(buckets.len(), 2);
+    assert_eq!(buckets["even"], [8, 2, 4]);
+    assert_eq!(buckets["odd"], [3, 5, 7]);
+
+    // Buckets can be any `Default + Extend` collection.
+    let words = ["pear", "fig", "plum", "pear"];
+    let by_len = collect![classify (|w: &&str| w.len()) from words as BTreeMap<_, HashSet<_>>];
+    assert_eq!(by_len[&3], collect!["fig"]);
+    assert_eq!(by_len[&4], collect!["pear", "plum"]);
+}
+
+#[test]
+fn test_counted() {
+    let (m, n) = collect![counted as HashMap<_, _>: "a" => 1, "b" => 2, "a" => 3];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 3, "b" => 2]);
+    assert_eq!(n, 1);
+    assert!(m.capacity() >= 3);
+
+    let (m, n) = collect![counted as BTreeMap<_, _>: 1 => 'x', 2 => 'y',];
+    assert_eq!(m, collect![1 => 'x', 2 => 'y']);
+    assert_eq!(n, 0);
+
+    let (_, n) = collect![counted as BTreeMap<_, _>: 0 => (), 0 => (), 0 => ()];
+    assert_eq!(n, 2);
+}
+
+#[test]
+fn test_heapify() {
+    let heapified = collect![heapify: 3, 1, 4, 1, 5, 9, 2, 6];
+    let pushed = collect![as BinaryHeap<_>: 3, 1, 4, 1, 5, 9, 2, 6];
+    assert_eq!(heapified.into_sorted_vec(), pushed.into_sorted_vec());
+
+    let src = vec![5, 3, 8, 1];
+    let ptr = src.as_ptr();
+    let heapified = collect![heapify from src];
+    assert_eq!(heapified.as_slice().as_ptr(), ptr);
+    assert_eq!(heapified.into_sorted_vec(), [1, 3, 5, 8]);
+
+    let mut heapified = collect![heapify from 0..4];
+    let mut pushed: BinaryHeap<i32> = collect![..0..4];
+    while let Some(v) = pushed.pop() {
+        assert_eq!(heapified.pop(), Some(v));
+    }
+    assert!(heapified.is_empty());
+}
+
+#[test]
+fn test_upper_lower() {
+    let s = collect![upper as String: "hello", "world"];
+    assert_eq!(s, "HELLOWORLD");
+    assert!(s.capacity() >= 10);
+
+    assert_eq!(collect![lower: "MiXeD", " Case"], "mixed case");
+    assert_eq!(collect![upper: 'a', 'b'], "AB");
+    assert_eq!(collect![lower: String::from("ÀB")], "àb");
+
+    // Some characters change length.
+    let s = collect![upper: "straße"];
+    assert_eq!(s, "STRASSE");
+    assert_eq!(s.len(), 7);
+    assert_eq!(collect![upper: 'ß'], "SS");
+
+    // Lowercasing a whole string turns a final capital sigma into 'ς'.
+    assert_eq!(collect![lower: "ΟΔΟΣ"], "οδος");
+
+    let s: String = collect![upper:];
+    assert!(s.is_empty());
+}
+
+#[test]
+fn test_chars() {
+    let v = collect![chars from "hello"];
+    assert_eq!(v, ['h', 'e', 'l', 'l', 'o']);
+    assert_eq!(v.capacity(), 5);
+
+    let v = collect![chars from String::from("größe")];
+    assert_eq!(v, ['g', 'r', 'ö', 'ß', 'e']);
+    assert!(v.capacity() >= 5);
+
+    let v = collect![chars from ""];
+    assert!(v.is_empty());
+
+    // A function called `chars` is still just an element.
+    fn chars(n: usize) -> usize { n + 1 }
+    let v: Vec<usize> = collect![chars(1)];
+    assert_eq!(v, [2]);
+}
+
+#[test]
+fn test_hex() {
+    let v = collect![hex from "deadBEEF"];
+    assert_eq!(v, [0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(v.capacity(), 4);
+
+    assert_eq!(collect![hex from String::from("00ff")], [0x00, 0xff]);
+    assert_eq!(collect![hex from ""], []);
+
+    assert_eq!(collect![try_hex from "abc"], Err(HexError::OddLength(3)));
+    assert_eq!(collect![try_hex from "0g"], Err(HexError::InvalidChar { index: 1, ch: 'g' }));
+    assert_eq!(collect![try_hex from "0ü"], Err(HexError::InvalidChar { index: 1, ch: 'ü' }));
+    assert_eq!(collect![try_hex from "00x"], Err(HexError::InvalidChar { index: 2, ch: 'x' }));
+
+    // Functions called `hex` and `try_hex` are still just elements.
+    fn hex(n: u8) -> u8 { n * 16 }
+    fn try_hex(n: u8) -> Option<u8> { n.checked_mul(16) }
+    let v: Vec<u8> = collect![hex(1), hex(2)];
+    assert_eq!(v, [16, 32]);
+    let v: Vec<Option<u8>> = collect![try_hex(16)];
+    assert_eq!(v, [None]);
+}
+
+#[test]
+#[should_panic(expected = "collect![hex]: odd number of hex digits (3)")]
+fn test_hex_odd_length() {
+    let _ = collect![hex from "abc"];
+}
+
+#[test]
+#[should_panic(expected = "collect![hex]: invalid hex digit 'z' at index 2")]
+fn test_hex_invalid_char() {
+    let _ = collect![hex from "00zz"];
+}
+
+#[test]
+fn test_bits() {
+    let v = collect![bits from "10110001"];
+    assert_eq!(v, vec![0b1011_0001]);
+    assert_eq!(v.capacity(), 1);
+
+    // A partial final byte is padded with zeros.
+    assert_eq!(collect![bits from "101100011111"], vec![0b1011_0001, 0b1111_0000]);
+    assert_eq!(collect![bits from String::from("1")], vec![0b1000_0000]);
+    assert!(collect![bits from ""].is_empty());
+
+    assert_eq!(collect![try_bits from "0102"], Err(BitsError { index: 3, ch: '2' }));
+    assert_eq!(collect![try_bits from "0é"], Err(BitsError { index: 1, ch: 'é' }));
+
+    // Functions called `bits` and `try_bits` are still just elements.
+    fn bits(n: u32) -> u32 { n.count_ones() }
+    fn try_bits(n: u32) -> Option<u32> { n.checked_sub(1) }
+    let v: Vec<u32> = collect![bits(7), bits(8)];
+    assert_eq!(v, [3, 1]);
+    let v: Vec<Option<u32>> = collect![try_bits(0)];
+    assert_eq!(v, [None]);
+}
+
+#[test]
+#[should_panic(expected = "collect![bits]: invalid bit 'x' at index 3")]
+fn test_bits_invalid() {
+    let _ = collect![bits from "101x"];
+}
+
+#[test]
+fn test_flatten() {
+    let v = collect![flatten: [1, 2], [3, 4], [5, 6]];
+    assert_eq!(v, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(v.capacity(), 6);
+
+    let v = collect![flatten: [1], [], [2, 3, 4], [5, 6],];
+    assert_eq!(v, [1, 2, 3, 4, 5, 6]);
+    assert_eq!(v.capacity(), 6);
+
+    let s = collect![flatten as String: ['a', 'b'], "cd".chars()];
+    assert_eq!(s, "abcd");
+}
+
+#[test]
+fn test_flatten_all() {
+    let nested: Vec<Vec<i32>> = vec![vec![], vec![1, 2], vec![], vec![3], vec![]];
+    let v = collect![flatten_all: nested];
+    assert_eq!(v, [1, 2, 3]);
+    assert_eq!(v.capacity(), 3);
+
+    let nested = vec![collect![as VecDeque<_>: 'a'], collect![as VecDeque<_>: 'b', 'c']];
+    let d = collect![flatten_all as VecDeque<_>: nested];
+    assert_eq!(d, ['a', 'b', 'c']);
+
+    let empty: Vec<Vec<i32>> = vec![];
+    assert!(collect![flatten_all: empty].is_empty());
+}
+
+#[test]
+fn test_btree_union() {
+    let a: BTreeSet<i32> = collect![5, 1, 3];
+    let b: BTreeSet<i32> = collect![3, 4, 5];
+    let c: BTreeSet<i32> = collect![10, 11];
+
+    let u = collect![btree_union: a, b];
+    assert_eq!(u.into_iter().collect::<Vec<_>>(), [1, 3, 4, 5]);
+
+    let u = collect![btree_union: &a, c,];
+    assert_eq!(u.into_iter().collect::<Vec<_>>(), [1, 3, 5, 10, 11]);
+
+    // The sources are only borrowed.
+    assert_eq!(a.len(), 3);
+}
+
+#[test]
+fn test_btree_intersection() {
+    let a: BTreeSet<i32> = collect![5, 1, 3];
+    let b: BTreeSet<i32> = collect![3, 4, 5];
+    let c: BTreeSet<i32> = collect![10, 11];
+
+    let i = collect![btree_intersection: a, b];
+    assert_eq!(i.into_iter().collect::<Vec<_>>(), [3, 5]);
+
+    let i = collect![btree_intersection: a, b, c];
+    assert!(i.is_empty());
+
+    let i = collect![btree_intersection: &a];
+    assert_eq!(i, a);
+}
+
+#[test]
+fn test_parse() {
+    let v = collect![parse as Vec<i32>: "1", "2", "-3"];
+    assert_eq!(v, Ok(vec![1, 2, -3]));
+
+    let err = collect![parse as Vec<i32>: "1", "2", "x", "y"].unwrap_err();
+    assert_eq!(err.index, 2);
+    assert_eq!(err.error, "x".parse::<i32>().unwrap_err());
+    assert_eq!(err.to_string(), "failed to parse element 2: invalid digit found in string");
+
+    let set = collect![parse as BTreeSet<u8>: String::from("2"), String::from("1"), String::from("2")];
+    assert_eq!(set, Ok(collect![1, 2]));
+}
+
+#[test]
+fn test_csv() {
+    let v = collect![csv from "1,2,3"];
+    assert_eq!(v, ["1", "2", "3"]);
+    assert_eq!(v.capacity(), 3);
+
+    let line = String::from(" a ,, b\t");
+    let d = collect![csv as VecDeque<_>: &line];
+    assert_eq!(d, ["a", "", "b"]);
+
+    assert_eq!(collect![csv from ""], [""]);
+
+    // A function called `csv` is still just an element.
+    fn csv(fields: &[&str]) -> String { fields.join(",") }
+    let v: Vec<String> = collect![csv(&["a", "b"])];
+    assert_eq!(v, ["a,b"]);
+}
+
+#[test]
+fn test_csv_parse() {
+    assert_eq!(collect![csv_parse as Vec<i32>: "1, 2,3"], Ok(vec![1, 2, 3]));
+
+    let r = collect![csv_parse as Vec<u8>: "1, 2, 300, x"];
+    let err = r.unwrap_err();
+    assert_eq!(err.index, 2);
+    assert_eq!(err.to_string(), "failed to parse element 2: number too large to fit in target type");
+
+    let r = collect![csv_parse as HashSet<char>: "a, b, a"];
+    assert_eq!(r, Ok(collect!['a', 'b']));
+}
+
+#[test]
+fn test_invert() {
+    let m: HashMap<i32, &str> = collect![1 => "a", 2 => "b"];
+    let inv = collect![invert from m];
+    assert_eq!(inv, collect![as HashMap<_, _>: "a" => 1, "b" => 2]);
+    assert!(inv.capacity() >= 2);
+
+    let m: BTreeMap<i32, char> = collect![1 => 'x', 2 => 'y', 3 => 'x'];
+    let inv = collect![invert from m as BTreeMap<_, _>];
+    assert_eq!(inv, collect!['x' => 3, 'y' => 2]);
+
+    let inv = collect![invert from (vec![(1, 'a'), (2, 'b')]) as BTreeMap<_, _>];
+    assert_eq!(inv, collect!['a' => 1, 'b' => 2]);
+
+    let inv: HashMap<char, i32> = collect![invert from vec![(1, 'a')].into_iter()];
+    assert_eq!(inv[&'a'], 1);
+}
+
+#[test]
+fn test_seq() {
+    let v = collect![seq from 1, |&x| if x < 100 { Some(x * 2) } else { None }];
+    assert_eq!(v, [1, 2, 4, 8, 16, 32, 64, 128]);
+
+    // The seed is kept even if there's no successor.
+    let v = collect![seq from "only", |_| None];
+    assert_eq!(v, ["only"]);
+
+    let d = collect![seq from 10u32, (|&x: &u32| x.checked_sub(4)) as VecDeque<_>];
+    assert_eq!(d, [10, 6, 2]);
+
+    // A function called `seq` is still just an element.
+    fn seq(x: i32) -> i32 { x * 10 }
+    let v: Vec<i32> = collect![seq(1), seq(2)];
+    assert_eq!(v, [10, 20]);
+}
+
+#[test]
+fn test_intersperse() {
+    let v: Vec<i32> = collect![intersperse 0:];
+    assert!(v.is_empty());
+
+    let v = collect![intersperse 0: 1];
+    assert_eq!(v, [1]);
+
+    let v = collect![intersperse 0: 1, 2, 3,];
+    assert_eq!(v, [1, 0, 2, 0, 3]);
+
+    let sep = String::from(", ");
+    let s = collect![intersperse (&*sep) as String: "a", "b", "c"];
+    assert_eq!(s, "a, b, c");
+
+    let v = collect![intersperse (String::from("-")) as Vec<String>: String::from("x"), String::from("y")];
+    assert_eq!(v, ["x", "-", "y"]);
+
+    // `SEP` is evaluated exactly once, even when there are no elements to separate.
+    let mut evals = 0;
+    let v: Vec<i32> = collect![intersperse ({ evals += 1; 0 }) as Vec<i32>];
+    assert!(v.is_empty());
+    assert_eq!(evals, 1);
+
+    let v = collect![intersperse ({ evals += 1; 0 }): 1, 2];
+    assert_eq!(v, [1, 0, 2]);
+    assert_eq!(evals, 2);
+}
+
+#[test]
+fn test_weave() {
+    let v = collect![weave (["-", "+"].iter().cycle().cloned()): "a", "b", "c", "d"];
+    assert_eq!(v, ["a", "-", "b", "+", "c", "-", "d"]);
+    assert_eq!(v.capacity(), 7);
+
+    // Once the separators run out, the rest of the elements are added without them.
+    let v = collect![weave (vec![0]): 1, 2, 3];
+    assert_eq!(v, [1, 0, 2, 3]);
+
+    let seps = [9, 8, 7];
+    let d = collect![weave (seps.iter().cloned()) as VecDeque<_>: 1, 2];
+    assert_eq!(d, [1, 9, 2]);
+
+    let v = collect![weave (vec![0]): 1];
+    assert_eq!(v, [1]);
+
+    let v: Vec<i32> = collect![weave (vec![0]):];
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_fmt() {
+    use std::fmt;
+
+    struct Point(i32, i32);
+
+    impl fmt::Display for Point {
+        fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+            write!(fmt, "({}, {})", self.0, self.1)
+        }
+    }
+
+    assert_eq!(collect![fmt as String: 1, 2, 3], "123");
+    assert_eq!(collect![fmt: "x = ", Point(1, -2), '!'], "x = (1, -2)!");
+    assert_eq!(collect![fmt:], "");
+
+    assert_eq!(collect![fmt_join ", ": 1, 2, 3], "1, 2, 3");
+    assert_eq!(collect![fmt_join " -> " as String: Point(0, 0), Point(1, 1),], "(0, 0) -> (1, 1)");
+    assert_eq!(collect![fmt_join '|': "only"], "only");
+    assert_eq!(collect![fmt_join '|':], "");
+}
+
+#[test]
+fn test_keys() {
+    let m = collect![keys as HashMap<&str, i32>: "a", "b", "c"];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 0, "b" => 0, "c" => 0]);
+
+    let mut m = collect![keys as HashMap<&str, Vec<i32>>: "x", "y",];
+    assert_eq!(m.len(), 2);
+    assert!(m.values().all(|v| v.is_empty()));
+    m.get_mut("x").unwrap().push(1);
+    assert_eq!(m["x"], [1]);
+
+    let m: BTreeMap<i32, String> = collect![keys: 2, 1];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), [(1, String::new()), (2, String::new())]);
+
+    // A variable called `keys` is still just an element.
+    let keys = [3, 4];
+    assert_eq!(collect![as Vec<i32>: keys[0], keys[1]], [3, 4]);
+    let v: Vec<i32> = collect![keys[1], keys[0]];
+    assert_eq!(v, [4, 3]);
+}
+
+#[test]
+fn test_take() {
+    let v = collect![take 3: 1, 2, 3, 4, 5];
+    assert_eq!(v, [1, 2, 3]);
+    assert_eq!(v.capacity(), 3);
+
+    // Clamped to the number of elements.
+    let v = collect![take 10: 1, 2];
+    assert_eq!(v, [1, 2]);
+    assert_eq!(v.capacity(), 2);
+
+    let v: Vec<i32> = collect![take 0: 1, 2];
+    assert!(v.is_empty());
+
+    // Dropped elements are never evaluated.
+    let mut evaluated = vec![];
+    let n = 2;
+    let s = collect![take n as String: { evaluated.push(0); 'a' }, { evaluated.push(1); 'b' }, { evaluated.push(2); 'c' }];
+    assert_eq!(s, "ab");
+    assert_eq!(evaluated, [0, 1]);
+}
+
+#[test]
+fn test_env_pairs() {
+    let m = collect![env_pairs: "A=1", "B=2", "C="];
+    assert_eq!(m, collect![as HashMap<_, _>: "A" => "1", "B" => "2", "C" => ""]);
+
+    // Only the first `=` splits.
+    let m = collect![env_pairs as BTreeMap<_, _>: "OPTS=a=b", "X=1"];
+    assert_eq!(m, collect!["OPTS" => "a=b", "X" => "1"]);
+
+    let owned = String::from("K=V");
+    let m = collect![env_pairs: &owned];
+    assert_eq!(m["K"], "V");
+
+    let err = collect![try_env_pairs: "A=1", "B", "C"].unwrap_err();
+    assert_eq!(err, EnvPairError { index: 1, pair: String::from("B") });
+    assert_eq!(err.to_string(), "element 1 (\"B\") is missing an `=`");
+
+    assert_eq!(collect![try_env_pairs: "A=1"], Ok(collect![as HashMap<_, _>: "A" => "1"]));
+}
+
+#[test]
+#[should_panic(expected = "collect![env_pairs]: element 0 (\"oops\") is missing an `=`")]
+fn test_env_pairs_missing_separator() {
+    let _ = collect![env_pairs: "oops"];
+}
+
+#[test]
+fn test_split_on() {
+    let data: &[u8] = b"a,bc,d";
+    let v = collect![split_on b',' from data];
+    assert_eq!(v, [&b"a"[..], b"bc", b"d"]);
+    assert_eq!(v.capacity(), 3);
+
+    // Leading, trailing and consecutive delimiters all produce empty segments.
+    let data: &[u8] = b",a,,b,";
+    let v = collect![split_on b',' from data];
+    assert_eq!(v, [&b""[..], b"a", b"", b"b", b""]);
+
+    // An empty slice is a single empty segment.
+    let empty: &[u8] = &[];
+    let v = collect![split_on b',' from empty];
+    assert_eq!(v, [&b""[..]]);
+
+    let words = vec![1, 0, 2, 3, 0];
+    let d = collect![split_on 0 from (&words) as VecDeque<_>];
+    assert_eq!(d, [&[1][..], &[2, 3][..], &[][..]]);
+}
+
+#[test]
+fn test_windows() {
+    let v = collect![windows from 0..5];
+    assert_eq!(v, vec![(0, 1), (1, 2), (2, 3), (3, 4)]);
+    assert_eq!(v.capacity(), 4);
+
+    let v: Vec<(i32, i32)> = collect![windows from vec![1]];
+    assert_eq!(v, vec![]);
+
+    let v: Vec<(i32, i32)> = collect![windows from 0..0];
+    assert_eq!(v, vec![]);
+
+    let v = collect![windows from (vec!["a", "b", "c"]) as VecDeque<_>];
+    assert_eq!(v, collect![as VecDeque<_>: ("a", "b"), ("b", "c")]);
+
+    let words = [String::from("x"), String::from("y")];
+    let v = collect![windows from (words.iter().cloned())];
+    assert_eq!(v, vec![(String::from("x"), String::from("y"))]);
+}
+
+#[test]
+fn test_pairs() {
+    let m = collect![pairs as BTreeMap<_, _>: "c", 3, "a", 1, "b", 2];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![("a", 1), ("b", 2), ("c", 3)]);
+
+    let m = collect![pairs: "a", 1, "b", 2,];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 1, "b" => 2]);
+
+    let m: HashMap<&str, i32> = collect![pairs:];
+    assert!(m.is_empty());
+
+    // Enough entries to go through the chunked path more than once.
+    let m = collect![pairs as BTreeMap<_, _>:
+        0, 'a', 1, 'b', 2, 'c', 3, 'd', 4, 'e', 5, 'f', 6, 'g', 7, 'h', 8, 'i', 9, 'j'];
+    assert_eq!(m.len(), 10);
+    assert_eq!(m[&9], 'j');
+}
+
+#[test]
+fn test_zip_arrays() {
+    let ks = [1, 2, 3];
+    let vs = ["a", "b", "c"];
+    let m = collect![zip_arrays ks, vs as HashMap<_, _>];
+    assert_eq!(m, collect![1 => "a", 2 => "b", 3 => "c"]);
+    assert!(m.capacity() >= 3);
+
+    // Extra elements of the longer array are ignored.
+    let m = collect![zip_arrays ["x", "y", "z"], (vec![1, 2]) as BTreeMap<_, _>];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![("x", 1), ("y", 2)]);
+
+    let names = vec![String::from("a"), String::from("b")];
+    let m = collect![zip_arrays &names, [10, 20, 30]];
+    assert_eq!(m[&names[1]], 20);
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn test_zip_merge() {
+    let ks = vec!["x", "y", "x", "x"];
+    let m = collect![zip_merge ks, [1, 2, 3, 4] with (|a, b| a + b)];
+    assert_eq!(m, collect!["x" => 8, "y" => 2]);
+
+    // The resolver sees the existing value first.
+    let vs = vec![String::from("a"), String::from("b"), String::from("c")];
+    let m = collect![zip_merge [1, 1, 2], vs with (|old: String, new: String| old + &new) as BTreeMap<_, _>];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![(1, String::from("ab")), (2, String::from("c"))]);
+
+    let mut calls = 0;
+    let m = collect![zip_merge 0..3, ["a", "b", "c", "d"] with (|_, b| { calls += 1; b })];
+    assert_eq!(m.len(), 3);
+    assert_eq!(calls, 0);
+}
+
+#[test]
+fn test_map_values() {
+    let m = collect![map_values (|k| k * k) over 1..=3];
+    assert_eq!(m, collect![as HashMap<_, _>: 1 => 1, 2 => 4, 3 => 9]);
+    assert!(m.capacity() >= 3);
+
+    let names = vec!["ab", "c"];
+    let m = collect![map_values (|s: &&str| s.len()) over names as BTreeMap<_, _>];
+    assert_eq!(m, collect!["ab" => 2, "c" => 1]);
+}
+
+#[test]
+fn test_scan() {
+    let v = collect![scan 0, (|acc: &mut i32, x| { *acc += x; Some(*acc) }) over 1..=4];
+    assert_eq!(v, vec![1, 3, 6, 10]);
+    assert_eq!(v.capacity(), 4);
+
+    // Stops at the first `None`.
+    let v = collect![scan 0, (|acc: &mut i32, x| {
+        *acc += x;
+        if *acc > 5 { None } else { Some(*acc) }
+    }) over 1..=10];
+    assert_eq!(v, vec![1, 3]);
+
+    // Nothing is reserved for the source's size, which could be far more than the scan produces.
+    let v = collect![scan 0u64, (|_, x| if x < 3 { Some(x) } else { None }) over (0..u64::MAX)];
+    assert_eq!(v, vec![0, 1, 2]);
+
+    // An unbounded source has no upper bound to reserve for.
+    let v = collect![scan 0, (|acc: &mut i32, x| {
+        *acc += x;
+        if *acc > 5 { None } else { Some(*acc) }
+    }) over (1..)];
+    assert_eq!(v, vec![1, 3]);
+
+    let v = collect![scan 1, (|acc: &mut u64, _| { *acc *= 2; Some(*acc) }) over (0..3) as VecDeque<_>];
+    assert_eq!(v, collect![as VecDeque<_>: 2, 4, 8]);
+}
+
+#[test]
+fn test_ci_map() {
+    let m = collect![ci_map: "Content-Type" => "x", "Accept" => "*/*", "CONTENT-TYPE" => "y"];
+    assert_eq!(m.len(), 2);
+    assert_eq!(m["content-type"], "y");
+    assert_eq!(m["accept"], "*/*");
+
+    let key = String::from("X-Mixed");
+    let m = collect![ci_map as BTreeMap<_, _>: key => 1, "ÄB" => 2];
+    assert_eq!(m, collect![String::from("x-mixed") => 1, String::from("äb") => 2]);
+}
+
+#[test]
+fn test_prefix_map() {
+    let m = collect![prefix "db." map: "host" => "localhost", "port" => "5432"];
+    assert_eq!(m, collect![String::from("db.host") => "localhost", String::from("db.port") => "5432"]);
+
+    // The prefix is evaluated once, and applied exactly once to each key.
+    let mut evaluated = 0;
+    let key = String::from("b");
+    let m = collect![prefix ({ evaluated += 1; String::from("x.") }) map as BTreeMap<_, _>: "a" => 1, key => 2, "x.c" => 3];
+    assert_eq!(evaluated, 1);
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![
+        (String::from("x.a"), 1), (String::from("x.b"), 2), (String::from("x.x.c"), 3),
+    ]);
+
+    let m: HashMap<String, i32> = collect![prefix "db." map:];
+    assert!(m.is_empty());
+}
+
+#[test]
+fn test_obj() {
+    let m = collect![obj: name => "alice", age => "30",];
+    assert_eq!(m, collect![as HashMap<_, _>: "name" => "alice", "age" => "30"]);
+
+    let port = 5432;
+    let m = collect![obj as BTreeMap<_, _>: port => port, r#loop => 1];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![("port", 5432), ("r#loop", 1)]);
+
+    let m: HashMap<&str, i32> = collect![obj:];
+    assert!(m.is_empty());
+}
+
+#[test]
+fn test_fields_of() {
+    let m = collect![fields_of { b: 2, a: 1 }];
+    assert_eq!(m, collect![as BTreeMap<_, _>: "a" => 1, "b" => 2]);
+
+    let name = "alice";
+    let m = collect![fields_of { name: name, role: "admin", } as HashMap<_, _>];
+    assert_eq!(m, collect![obj: name => "alice", role => "admin"]);
+
+    let m: BTreeMap<&str, i32> = collect![fields_of {}];
+    assert!(m.is_empty());
+}
+
+#[test]
+fn test_bounded() {
+    let d = collect![bounded 3 as VecDeque<_>: 1, 2, 3, 4, 5];
+    assert_eq!(d, [3, 4, 5]);
+    assert_eq!(d.capacity(), VecDeque::<i32>::with_capacity(3).capacity());
+
+    // Fewer elements than the bound are all kept.
+    let d = collect![bounded 4: 'a', 'b'];
+    assert_eq!(d, ['a', 'b']);
+
+    // Evicted elements are still evaluated.
+    let mut evaluated = vec![];
+    let d = collect![bounded 0: { evaluated.push(0); 0 }, { evaluated.push(1); 1 }];
+    assert!(d.is_empty());
+    assert_eq!(evaluated, [0, 1]);
+}
+
+#[test]
+fn test_sorted_pairs() {
+    let v: Vec<(i32, &str)> = collect![sorted_pairs: 3 => "c", 1 => "a", 2 => "b"];
+    assert_eq!(v, vec![(1, "a"), (2, "b"), (3, "c")]);
+
+    // Equal keys are all kept, in their original order.
+    let v = collect![sorted_pairs: "b" => 1, "a" => 2, "b" => 3,];
+    assert_eq!(v, vec![("a", 2), ("b", 1), ("b", 3)]);
+}
+
+#[test]
+fn test_table() {
+    let m = collect![table (0..3): |i| (i, i * i)];
+    assert_eq!(m, collect![as HashMap<_, _>: 0 => 0, 1 => 1, 2 => 4]);
+    assert!(m.capacity() >= 3);
+
+    let m = collect![table (1..=3) as BTreeMap<_, _>: |i| (format!("k{}", i), i * 10)];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), vec![
+        (String::from("k1"), 10),
+        (String::from("k2"), 20),
+        (String::from("k3"), 30),
+    ]);
+
+    let empty = 2..2;
+    let m = collect![table empty: |i| (i, i)];
+    assert!(m.is_empty());
+}
+
+#[test]
+fn test_gen() {
+    let mut calls = 0;
+    let v = collect![gen 5: || { calls += 1; calls * 10 }];
+    assert_eq!(calls, 5);
+    assert_eq!(v.len(), 5);
+    assert_eq!(v, [10, 20, 30, 40, 50]);
+    assert!(v.capacity() >= 5);
+
+    let n = 2;
+    let d = collect![gen n as VecDeque<_>: String::new];
+    assert_eq!(d, ["", ""]);
+
+    let v = collect![gen 0: || -> i32 { panic!("called") }];
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_nested() {
+    let m = collect![nested: "a", "x" => 1, "a", "y" => 2, "b", "z" => 3];
+    let expected: HashMap<_, HashMap<_, _>> = collect![
+        "a" => collect!["x" => 1, "y" => 2],
+        "b" => collect!["z" => 3],
+    ];
+    assert_eq!(m, expected);
+
+    let m = collect![nested as BTreeMap<_, BTreeMap<_, _>>: 2, 'b' => "2b", 1, 'a' => "1a", 2, 'a' => "2a",];
+    assert_eq!(m.keys().collect::<Vec<_>>(), vec![&1, &2]);
+    assert_eq!(m[&2].values().collect::<Vec<_>>(), vec![&"2a", &"2b"]);
+}
+
+#[test]
+fn test_btree_merge() {
+    let a: BTreeMap<_, _> = collect![1 => "a", 2 => "a", 3 => "a"];
+    let b: BTreeMap<_, _> = collect![2 => "b", 4 => "b"];
+    let c: BTreeMap<_, _> = collect![3 => "c", 4 => "c", 5 => "c"];
+    let kept = a.clone();
+
+    let m = collect![btree_merge: a, b, c,];
+    assert_eq!(m, collect![as BTreeMap<_, _>: 1 => "a", 2 => "b", 3 => "c", 4 => "c", 5 => "c"]);
+
+    let m = collect![btree_merge: kept.clone()];
+    assert_eq!(m, kept);
+}
+
+#[test]
+fn test_validate() {
+    assert_eq!(collect![validate (|x: &i32| *x > 0): 1, 2, 3], Ok(vec![1, 2, 3]));
+
+    let mut evaluated = vec![];
+    let mut eval = |x: i32| { evaluated.push(x); x };
+    let r = collect![validate (|x: &i32| *x > 0): eval(1), eval(-2), eval(3)];
+    assert_eq!(r, Err(ValidationError { index: 1 }));
+    assert_eq!(r.unwrap_err().to_string(), "element 1 failed validation");
+    // Elements after the failure aren't evaluated.
+    assert_eq!(evaluated, [1, -2]);
+
+    let r = collect![validate (|s: &&str| !s.is_empty()) as VecDeque<_>: "a", "b",];
+    assert_eq!(r, Ok(collect![as VecDeque<_>: "a", "b"]));
+
+    let r: Result<Vec<i32>, _> = collect![validate (|_: &i32| false):];
+    assert_eq!(r, Ok(vec![]));
+}
+
+#[test]
+fn test_drop_eq() {
+    let v = collect![drop_eq 0 as Vec<_>: 1, 0, 2, 0, 3];
+    assert_eq!(v, [1, 2, 3]);
+    assert!(v.capacity() >= 5);
+
+    // The sentinel can appear at either end, or make up the whole list.
+    assert_eq!(collect![drop_eq 0: 0, 0, 1, 2, 0], [1, 2]);
+    assert_eq!(collect![drop_eq "": "", "a", ""], ["a"]);
+    assert!(collect![drop_eq 0: 0, 0].is_empty());
+
+    let skip = String::from("-");
+    let d = collect![drop_eq (&*skip) as VecDeque<_>: "a", "-", "b"];
+    assert_eq!(d, ["a", "b"]);
+}
+
+#[test]
+fn test_arrays() {
+    let m = collect![arrays as HashMap<_, _>: [1, "a"], [2, "b"], [3, "c"],];
+    assert_eq!(m, collect![as HashMap<_, _>: 1 => "a", 2 => "b", 3 => "c"]);
+
+    let m: BTreeMap<String, i32> = collect![arrays: [String::from("x"), 1 + 1]];
+    assert_eq!(m["x"], 2);
+
+    // A variable called `arrays` is still just an element.
+    let arrays = [[1, 2], [3, 4]];
+    assert_eq!(collect![as Vec<[i32; 2]>: arrays[1], arrays[0]], [[3, 4], [1, 2]]);
+}
+
+#[test]
+fn test_key_set_value_set() {
+    let m: HashMap<_, _> = collect!["a" => 1, "b" => 2, "c" => 1];
+
+    let keys = collect![key_set from m];
+    assert_eq!(keys, collect![as HashSet<_>: "a", "b", "c"]);
+    assert!(keys.capacity() >= 3);
+
+    let values = collect![value_set from m];
+    assert_eq!(values, collect![as HashSet<_>: 1, 2]);
+
+    let m: BTreeMap<String, String> = collect![String::from("x") => String::from("1")];
+    let keys = collect![key_set from m as BTreeSet<_>];
+    assert_eq!(keys, collect![as BTreeSet<_>: String::from("x")]);
+    let values = collect![value_set from (&m) as BTreeSet<_>];
+    assert_eq!(values, collect![as BTreeSet<_>: String::from("1")]);
+    assert_eq!(m.len(), 1);
+}
+
+#[test]
+fn test_dense() {
+    let v = collect![dense 5 default 0: 1 => 10, 3 => 30];
+    assert_eq!(v, [0, 10, 0, 30, 0]);
+    assert_eq!(v.capacity(), 5);
+
+    // Later values overwrite earlier ones at the same index.
+    let n = 3;
+    let v = collect![dense n default (String::from("-")): 0 => String::from("a"), 0 => String::from("b"),];
+    assert_eq!(v, ["b", "-", "-"]);
+
+    let v: Vec<i32> = collect![dense 2 default 7:];
+    assert_eq!(v, [7, 7]);
+}
+
+#[test]
+#[should_panic(expected = "collect![dense]: index 5 is out of bounds for length 5")]
+fn test_dense_out_of_bounds() {
+    let _ = collect![dense 5 default 0: 1 => 10, 5 => 50];
+}
+
+#[test]
+fn test_interleave() {
+    let v = collect![interleave: (1, 2, 3), (10, 20, 30)];
+    assert_eq!(v, vec![1, 10, 2, 20, 3, 30]);
+
+    let v = collect![interleave as VecDeque<_>: ("a",), ("b",),];
+    assert_eq!(v, collect![as VecDeque<_>: "a", "b"]);
+
+    let v: Vec<i32> = collect![interleave: (), ()];
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_self_map() {
+    let m = collect![self_map: 1, 2, 3];
+    assert_eq!(m, collect![as HashMap<_, _>: 1 => 1, 2 => 2, 3 => 3]);
+    assert_eq!(m.capacity(), HashMap::<i32, i32>::with_capacity(3).capacity());
+
+    let mut calls = 0;
+    let m = collect![self_map as BTreeMap<_, _>: { calls += 1; String::from("a") }, String::from("b"),];
+    assert_eq!(calls, 1);
+    assert_eq!(m["a"], "a");
+    assert_eq!(m["b"], "b");
+}
+
+#[test]
+fn test_wrapped_values() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Mutex;
+
+    let m: HashMap<i32, RefCell<i32>> = collect![cell_values: 1 => 10, 2 => 20];
+    *m[&2].borrow_mut() *= 2;
+    assert_eq!(*m[&1].borrow(), 10);
+    assert_eq!(*m[&2].borrow(), 40);
+
+    let m: BTreeMap<&str, Mutex<Vec<i32>>> = collect![mutex_values as BTreeMap<_, _>: "a" => vec![], "b" => vec![1],];
+    m["a"].lock().unwrap().push(7);
+    assert_eq!(*m["a"].lock().unwrap(), [7]);
+
+    let m = collect![rc_values: "x" => String::from("shared")];
+    let shared: Rc<String> = m["x"].clone();
+    assert_eq!(Rc::strong_count(&shared), 2);
+}
+
+#[test]
+fn test_ordered_counts() {
+    let counts = collect![ordered_counts: 'b', 'a', 'b', 'c', 'b'];
+    assert_eq!(counts.keys().cloned().collect::<String>(), "abc");
+    assert_eq!(counts, collect![as BTreeMap<_, _>: 'a' => 1, 'b' => 3, 'c' => 1]);
+
+    let counts = collect![ordered_counts as HashMap<_, _>: "x", "y", "x",];
+    assert_eq!(counts, collect![as HashMap<_, _>: "x" => 2, "y" => 1]);
+
+    let counts: BTreeMap<i32, usize> = collect![ordered_counts:];
+    assert!(counts.is_empty());
+}
+
+#[test]
+fn test_no_overwrite() {
+    let m = collect![no_overwrite as HashMap<_, _>: "a" => 1, "b" => 2];
+    assert_eq!(m, collect!["a" => 1, "b" => 2]);
+    assert!(m.capacity() >= 2);
+
+    let m: HashMap<i32, i32> = collect![no_overwrite:];
+    assert!(m.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "collect![no_overwrite]: duplicate key: \"a\"")]
+fn test_no_overwrite_collision() {
+    let _ = collect![no_overwrite: "a" => 1, "b" => 2, "a" => 3];
+}
+
+#[test]
+fn test_sum_map() {
+    let m = collect![sum_map: "a" => 1, "a" => 2, "b" => 3, "a" => 4];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 7, "b" => 3]);
+
+    let m = collect![sum_map as BTreeMap<_, _>: 'x' => 0.5, 'y' => 1.5, 'z' => 2.0,];
+    assert_eq!(m, collect![as BTreeMap<_, _>: 'x' => 0.5, 'y' => 1.5, 'z' => 2.0]);
+}
+
+#[test]
+fn test_group_set() {
+    let m = collect![group_set: 1 => "a", 1 => "a", 2 => "b", 1 => "c"];
+    assert_eq!(m.len(), 2);
+    assert_eq!(m[&1], collect![as HashSet<_>: "a", "c"]);
+    assert_eq!(m[&2], collect![as HashSet<_>: "b"]);
+
+    let m = collect![group_set as BTreeMap<_, BTreeSet<_>>: "x" => 3, "y" => 1, "x" => 2, "x" => 3,];
+    assert_eq!(m["x"].iter().cloned().collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(m["y"].len(), 1);
+
+    let m: HashMap<i32, HashSet<i32>> = collect![group_set:];
+    assert!(m.is_empty());
+}
+
+#[test]
+fn test_try_values() {
+    let m: Result<HashMap<_, i32>, _> = collect![try_values: 1 => "10".parse(), 2 => "20".parse()];
+    let m = m.unwrap();
+    assert_eq!(m, collect![as HashMap<_, _>: 1 => 10, 2 => 20]);
+    assert!(m.capacity() >= 2);
+
+    let mut evaluated = vec![];
+    let r: Result<BTreeMap<_, _>, &str> = collect![try_values as BTreeMap<_, _>:
+        { evaluated.push(1); 1 } => Ok('a'),
+        { evaluated.push(2); 2 } => Err("bad"),
+        { evaluated.push(3); 3 } => Ok('c'),
+    ];
+    assert_eq!(r, Err("bad"));
+    assert_eq!(evaluated, [1, 2]);
+}
+
+#[test]
+fn test_try_gen() {
+    let v: Result<Vec<u32>, char> = collect![try_gen (0..4): |i| ::std::char::from_digit(i, 10).ok_or('?').map(|c| c as u32)];
+    assert_eq!(v, Ok(vec![48, 49, 50, 51]));
+
+    let mut called = vec![];
+    let r = collect![try_gen (0..10) as VecDeque<_>: |i| {
+        called.push(i);
+        if i < 3 { Ok(i * 10) } else { Err(i) }
+    }];
+    assert_eq!(r, Err(3));
+    assert_eq!(called, [0, 1, 2, 3]);
+
+    let empty: Result<Vec<i32>, ()> = collect![try_gen (0..0): Ok];
+    assert_eq!(empty, Ok(vec![]));
+
+    // An unbounded range is fine, so long as an error turns up.
+    let r: Result<Vec<usize>, usize> = collect![try_gen (0..): |i| if i < 3 { Ok(i) } else { Err(i) }];
+    assert_eq!(r, Err(3));
+}
+
+#[test]
+fn test_with_index() {
+    let v = collect![with_index: 10, 20, 30];
+    assert_eq!(v, vec![(0, 10), (1, 20), (2, 30)]);
+
+    let m = collect![with_index as BTreeMap<_, _>: 'x', 'y',];
+    assert_eq!(m, collect![0 => 'x', 1 => 'y']);
+
+    let v: Vec<(usize, i32)> = collect![with_index:];
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_rank_map() {
+    let ranks = collect![rank_map: "gold", "silver", "bronze"];
+    assert_eq!(ranks, collect!["gold" => 1, "silver" => 2, "bronze" => 3]);
+
+    // A repeated element keeps its last rank.
+    let ranks = collect![rank_map as BTreeMap<_, _>: 'a', 'b', 'a', 'c',];
+    assert_eq!(ranks.into_iter().collect::<Vec<_>>(), vec![('a', 3), ('b', 2), ('c', 4)]);
+
+    let ranks: HashMap<i32, usize> = collect![rank_map:];
+    assert!(ranks.is_empty());
+}
+
+#[test]
+fn test_display_join() {
+    assert_eq!(collect![display_join ", " from 1..4], "1, 2, 3");
+    assert_eq!(collect![display_join ", " from 0..0], "");
+    assert_eq!(collect![display_join '-' from Some(7)], "7");
+
+    struct Point(i32, i32);
+
+    impl std::fmt::Display for Point {
+        fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(fmt, "({}, {})", self.0, self.1)
+        }
+    }
+
+    let points = vec![Point(1, 2), Point(3, 4)];
+    let s = collect![display_join " -> " from &points];
+    assert_eq!(s, "(1, 2) -> (3, 4)");
+    assert!(s.capacity() >= 6);
+
+    let s = collect![display_join (1 + 1) from (vec!['a', 'b', 'c']) as String];
+    assert_eq!(s, "a2b2c");
+}
+
+#[test]
+fn test_pages() {
+    let p = collect![pages 2 from 0..6];
+    assert_eq!(p, collect![as HashMap<_, _>: 0 => vec![0, 1], 1 => vec![2, 3], 2 => vec![4, 5]]);
+    assert!(p.capacity() >= 3);
+
+    let p = collect![pages 4 from (vec!['a', 'b', 'c', 'd', 'e']) as BTreeMap<_, _>];
+    assert_eq!(p, collect![as BTreeMap<_, _>: 0 => vec!['a', 'b', 'c', 'd'], 1 => vec!['e']]);
+
+    let p: HashMap<usize, Vec<i32>> = collect![pages 3 from 0..0];
+    assert!(p.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "collect![pages]: page size must not be zero")]
+fn test_pages_zero() {
+    let _: HashMap<usize, Vec<i32>> = collect![pages 0 from 0..4];
+}
@@ -0,0 +1,70 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that the helper macros which accompany `collect!` work as advertised.
+*/
+
+#[macro_use] extern crate collect_mac;
+
+use collect_mac::DuplicateKeyError;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[test]
+fn test_push_into_map() {
+    let mut m: HashMap<&str, Vec<i32>> = collect![];
+    for i in 0..3 {
+        push_into_map!(m; "a" => i);
+    }
+    push_into_map!(m; "b" => 10, "a" => 3, "b" => 11,);
+
+    assert_eq!(m.len(), 2);
+    assert_eq!(m["a"], [0, 1, 2, 3]);
+    assert_eq!(m["b"], [10, 11]);
+
+    let mut m: BTreeMap<char, Vec<&str>> = collect![];
+    push_into_map!(m; 'x' => "one");
+    assert_eq!(m, collect!['x' => vec!["one"]]);
+}
+
+#[test]
+fn test_try_collect_map() {
+    let m = try_collect_map!["a" => 1, "b" => 2, "c" => 3,];
+    assert_eq!(m, Ok(collect![as HashMap<_, _>: "a" => 1, "b" => 2, "c" => 3]));
+
+    let mut evaluated = vec![];
+    let m = try_collect_map![
+        "a" => { evaluated.push(1); 1 },
+        "b" => { evaluated.push(2); 2 },
+        "a" => { evaluated.push(3); 3 },
+        "c" => { evaluated.push(4); 4 },
+    ];
+    let err = m.unwrap_err();
+    assert_eq!(err, DuplicateKeyError { key: "a" });
+    assert_eq!(err.to_string(), "duplicate key: \"a\"");
+    assert_eq!(evaluated, [1, 2]);
+}
+
+#[test]
+fn test_collect_set_dups() {
+    let (set, dups) = collect_set_dups!(1, 2, 2, 3, 1);
+    assert_eq!(set, collect![as HashSet<_>: 1, 2, 3]);
+    assert_eq!(dups, [2, 1]);
+
+    let (set, dups) = collect_set_dups!["a", "b",];
+    assert_eq!(set.len(), 2);
+    assert!(dups.is_empty());
+
+    let (set, dups): (HashSet<i32>, Vec<i32>) = collect_set_dups![];
+    assert!(set.is_empty() && dups.is_empty());
+
+    let (_, dups) = collect_set_dups![String::from("x"), String::from("x"), String::from("x")];
+    assert_eq!(dups, ["x", "x"]);
+}
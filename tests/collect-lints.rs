@@ -0,0 +1,246 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that every public form of `collect!` expands to code which compiles cleanly under strict lints.  The one exception is `in`, which needs the `nightly` feature, and is exercised by `collect-nightly.rs` instead.  If any form starts emitting `unused_mut`, `unused_variables` or the like, this crate will fail to build.
+*/
+#![deny(warnings, unused)]
+
+#[macro_use] extern crate collect_mac;
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+
+#[test]
+fn test_lints_empty() {
+    let a: Vec<i32> = collect![];
+    let b = collect![as Vec<i32>];
+    let c = collect![as Vec<i32>:];
+    assert_eq!((a.len(), b.len(), c.len()), (0, 0, 0));
+}
+
+#[test]
+fn test_lints_seq() {
+    let a: Vec<i32> = collect![0];
+    let b: Vec<i32> = collect![0, 1, 2,];
+    let c = collect![as HashSet<i32>: 0];
+    let d = collect![as HashSet<i32>: 0, 1, 2,];
+    assert_eq!((a.len(), b.len(), c.len(), d.len()), (1, 3, 1, 3));
+}
+
+#[test]
+fn test_lints_map() {
+    let a: HashMap<i32, &str> = collect![0 => "zero"];
+    let b: HashMap<i32, &str> = collect![0 => "zero", 1 => "one",];
+    let c = collect![as BTreeMap<i32, &str>: 0 => "zero"];
+    let d = collect![as BTreeMap<i32, &str>: 0 => "zero", 1 => "one",];
+    assert_eq!((a.len(), b.len(), c.len(), d.len()), (1, 2, 1, 2));
+}
+
+#[test]
+fn test_lints_spread() {
+    let xs = [1, 2];
+    let a: Vec<i32> = collect![..vec![0]];
+    let b = collect![as Vec<i32>: 0, ..vec![1, 2], 3];
+    let c = collect![cloned as Vec<i32>: ..&xs];
+    let d = collect![as BTreeMap<i32, &str>: ..vec![(0, "zero")], 1 => "one"];
+    assert_eq!((a.len(), b.len(), c.len(), d.len()), (1, 4, 2, 2));
+}
+
+#[test]
+fn test_lints_forms() {
+    let a = collect![fmt_join ", ":];
+    let b: Vec<i32> = collect![intersperse 0:];
+    let (c, d): (HashSet<i32>, Vec<i32>) = collect_set_dups![];
+    assert_eq!((a.len(), b.len(), c.len(), d.len()), (0, 0, 0, 0));
+
+    let e: Result<Vec<i32>, _> = collect![validate (|_: &i32| true):];
+    let f = collect![validate (|_: &i32| true): 1, 2];
+    assert_eq!((e.unwrap().len(), f.unwrap().len()), (0, 2));
+}
+
+#[test]
+fn test_lints_modifiers() {
+    let xs = [3, 1, 2];
+    let a = collect![sorted as Vec<i32>: 3, 1, 2];
+    let b = collect![sorted_by (|a: &i32, b: &i32| b.cmp(a)) as Vec<_>: 3, 1, 2];
+    let c = collect![sort_by_key (|s: &&str| s.len()) as Vec<_>: "ccc", "a"];
+    let d = collect![dedup_adjacent: 1, 1, 2];
+    let e = collect![dedup_by_key (|x: &i32| *x % 2): 1, 2, 3];
+    let f = collect![rotate 1 as VecDeque<i32>: 1, 2, 3];
+    let g = collect![pad_to 4 with 0 as Vec<i32>: 1];
+    let h = collect![terminate_with 0: 1, 2];
+    let i = collect![check (|c: &Vec<i32>| !c.is_empty()) as Vec<_>: 1];
+    let j = collect![try_check (|c: &Vec<i32>| c.is_empty()): 1].unwrap_err();
+    let k = collect![rev as Vec<i32>: 1, 2];
+    let l = collect![shrink as Vec<i32>: 1, 2];
+    let (m, _) = collect![with_hash as Vec<u8>: 1, 2];
+    let (n, _) = collect![timed as Vec<i32>: ..0..3];
+    let o = collect![limit 2 cloned as Vec<i32>: ..&xs];
+    assert_eq!(
+        (a.len(), b.len(), c.len(), d.len(), e.len(), f.len(), g.len(), h.len()),
+        (3, 3, 2, 2, 2, 3, 4, 3)
+    );
+    assert_eq!((i.len(), j.to_string().is_empty(), k.len(), l.len(), m.len(), n.len(), o.len()), (1, false, 2, 2, 2, 3, 2));
+}
+
+#[test]
+fn test_lints_building() {
+    let mut a = vec![1];
+    collect![into a; 2, 3];
+    let b = collect![outline as Vec<i32>: 1, 2];
+    let c = collect![prealloc as Vec<i32>: 1, 2];
+    let d = collect![via_from_iter as BTreeMap<i32, &str>: 1 => "a"];
+    let e = collect![ring 8 as VecDeque<i32>: 1];
+    let f = collect![reserve 8 as Vec<i32>: 1];
+    let g = collect![pow2 as Vec<i32>: 1, 2, 3];
+    let h = collect![headroom as HashSet<i32>: 1];
+    let i = collect![cap_from 4 as Vec<i32>: ..0..2];
+    let j = collect![as HashSet<i32>: expected(2); ..vec![1, 1, 2]];
+    let k = collect![assert_single_alloc as Vec<i32>: 1, 2];
+    let l = collect![assert_single_alloc as HashMap<i32, i32>: 1 => 2];
+    let m = collect![as [i32; 2]: 1, 2];
+    let n = collect![as Rc<[i32]>: 1, 2];
+    let o = collect![as Box<str>: "a", "b"];
+    let p = collect![from (vec![(1, 2)]) as HashMap<i32, i32>];
+    assert_eq!(
+        (a.len(), b.len(), c.len(), d.len(), e.len(), f.len(), g.len(), h.len()),
+        (3, 2, 2, 1, 1, 1, 3, 1)
+    );
+    assert_eq!(
+        (i.len(), j.len(), k.len(), l.len(), m.len(), n.len(), o.len(), p.len()),
+        (2, 2, 2, 1, 2, 2, 2, 1)
+    );
+}
+
+#[test]
+fn test_lints_generators() {
+    let mut next = 0;
+    let a = collect![range_map 0..3 => |k| k * 2];
+    let b = collect![map_values (|k: &i32| k * k) over 1..=3];
+    let c = collect![table (0..3): |i| (i, i)];
+    let d = collect![gen 2: || { next += 1; next }];
+    let e = collect![try_gen (0..2): Ok::<_, ()>].unwrap();
+    let f = collect![scan 0, (|acc: &mut i32, x| { *acc += x; Some(*acc) }) over 1..=3];
+    let g = collect![seq from 1, |&x| if x < 4 { Some(x * 2) } else { None }];
+    let h = collect![dense 3 default 0: 1 => 10];
+    let i = collect![take 1: 1, 2];
+    let j = collect![bounded 1: 1, 2];
+    let k = collect![pages 2 from 0..5];
+    let l = collect![windows from 0..3];
+    assert_eq!(
+        (a.len(), b.len(), c.len(), d.len(), e.len(), f.len(), g.len(), h.len()),
+        (3, 3, 3, 2, 2, 3, 3, 3)
+    );
+    assert_eq!((i.len(), j.len(), k.len(), l.len()), (1, 1, 3, 2));
+}
+
+#[test]
+fn test_lints_map_forms() {
+    let src: HashMap<i32, &str> = collect![1 => "a", 2 => "b"];
+    let a = collect![keys as HashMap<&str, i32>: "a"];
+    let b = collect![arrays as HashMap<_, _>: [1, "a"]];
+    let (c, _) = collect![counted as HashMap<_, _>: "a" => 1, "a" => 2];
+    let d = collect![ordered_counts: 'a', 'b', 'a'];
+    let e = collect![try_values: 1 => "1".parse::<i32>()].unwrap();
+    let f: HashMap<&str, i32> = collect![sum_map: "a" => 1, "a" => 2];
+    let g = collect![group_set: 1 => "a", 1 => "b"];
+    let h = collect![no_overwrite: "a" => 1];
+    let i = collect![invert from (src.clone()) as BTreeMap<_, _>];
+    let j = collect![key_set from src];
+    let k = collect![value_set from src];
+    let l = collect![sorted_pairs: 2 => "b", 1 => "a"];
+    let m = collect![cell_values: 1 => 10];
+    let n = collect![mutex_values: 1 => 10];
+    let o = collect![rc_values: 1 => 10];
+    let p = collect![self_map: 1, 2];
+    let q = collect![nested: "a", "x" => 1];
+    let r = collect![ci_map: "A" => 1];
+    let s = collect![prefix "p." map: "a" => 1];
+    let t = collect![obj: a => 1];
+    let u = collect![fields_of { x: 1 }];
+    let v = collect![pairs: "a", 1];
+    let w = collect![zip_arrays [1, 2], ["a", "b"]];
+    let x = collect![zip_merge ["a", "a"], [1, 2] with (|a, b| a + b)];
+    let y = collect![env_pairs: "A=1"];
+    let z = collect![try_env_pairs: "A=1"].unwrap();
+    assert_eq!(
+        (a.len(), b.len(), c.len(), d.len(), e.len(), f.len(), g.len(), h.len(), i.len()),
+        (1, 1, 1, 2, 1, 1, 1, 1, 2)
+    );
+    assert_eq!(
+        (j.len(), k.len(), l.len(), m.len(), n.len(), o.len(), p.len(), q.len(), r.len()),
+        (2, 2, 2, 1, 1, 1, 2, 1, 1)
+    );
+    assert_eq!(
+        (s.len(), t.len(), u.len(), v.len(), w.len(), x.len(), y.len(), z.len()),
+        (1, 1, 1, 1, 2, 1, 1, 1)
+    );
+
+    struct Item { id: u32 }
+    let items = vec![Item { id: 1 }, Item { id: 2 }];
+    let by_id = collect![index_by (|it: &Item| it.id) from items];
+    let counts = collect![group_count (|w: &&str| w.len()) from ["a", "b", "cc"]];
+    let parity = collect![classify (|n: &i32| n % 2 == 0) from 1..4];
+    let ranks = collect![rank_map: "a", "b"];
+    let indexed = collect![with_index as HashMap<_, _>: "a", "b"];
+    assert_eq!(
+        (by_id.len(), counts.len(), parity.len(), ranks.len(), indexed.len()),
+        (2, 2, 2, 2, 2)
+    );
+}
+
+#[test]
+fn test_lints_sequence_forms() {
+    let a: BTreeSet<i32> = collect![1, 2];
+    let b: BTreeSet<i32> = collect![2, 3];
+    let m1: BTreeMap<i32, &str> = collect![1 => "a"];
+    let m2: BTreeMap<i32, &str> = collect![2 => "b"];
+    let c = collect![heapify: 3, 1, 2];
+    let d = collect![heapify from vec![2, 1]];
+    let e = collect![flatten: [1, 2], [3]];
+    let f = collect![flatten_all: vec![vec![1], vec![2, 3]]];
+    let g = collect![btree_union: (a.clone()), (b.clone())];
+    let h = collect![btree_intersection: a, b];
+    let i = collect![btree_merge: m1, m2];
+    let j = collect![interleave: (1, 2), (10, 20)];
+    let k = collect![intersperse 0: 1, 2];
+    let l = collect![weave (vec![0]): 1, 2];
+    let m = collect![drop_eq 0: 0, 1];
+    let n = collect![validate (|x: &i32| *x > 0): 1].unwrap();
+    let bytes: &[u8] = b"a\nb";
+    let o = collect![split_on b'\n' from bytes];
+    assert_eq!(
+        (c.len(), d.len(), e.len(), f.len(), g.len(), h.len(), i.len(), j.len()),
+        (3, 2, 3, 3, 3, 1, 2, 4)
+    );
+    assert_eq!((k.len(), l.len(), m.len(), n.len(), o.len()), (3, 3, 1, 1, 2));
+}
+
+#[test]
+fn test_lints_string_forms() {
+    let a = collect![chars from "ab"];
+    let b = collect![hex from "00ff"];
+    let c = collect![try_hex from "00"].unwrap();
+    let d = collect![bits from "1010"];
+    let e = collect![try_bits from "1"].unwrap();
+    let f = collect![upper: "a", "b"];
+    let g = collect![lower as String: 'A'];
+    let h = collect![parse as Vec<i32>: "1", "2"].unwrap();
+    let i = collect![csv from "a,b"];
+    let j = collect![csv as VecDeque<_>: "a,b"];
+    let k = collect![csv_parse as Vec<i32>: "1,2"].unwrap();
+    let l = collect![fmt: 1, 'x'];
+    let m = collect![display_join ", " from 1..3];
+    assert_eq!(
+        (a.len(), b.len(), c.len(), d.len(), e.len(), f.len(), g.len()),
+        (2, 2, 1, 1, 1, 2, 1)
+    );
+    assert_eq!((h.len(), i.len(), j.len(), k.len(), l.len(), m.len()), (2, 2, 2, 2, 2, 4));
+}
@@ -0,0 +1,491 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that modifiers which act on the finished collection work as advertised, both alone and in combination.
+*/
+
+#[macro_use] extern crate collect_mac;
+
+use collect_mac::CheckError;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[test]
+fn test_sorted() {
+    assert_eq!(collect![sorted as Vec<_>: 3, 1, 2], [1, 2, 3]);
+    assert_eq!(collect![sorted as Vec<i32>: 1], [1]);
+    assert_eq!(collect![sorted as Vec<_>: "b", "c", "a", ..vec!["aa"]], ["a", "aa", "b", "c"]);
+
+    let xs: &[i32] = &[9, -1];
+    assert_eq!(collect![sorted cloned as Vec<i32>: 5, ..xs], [-1, 5, 9]);
+
+    let v: Vec<i32> = collect![sorted as Vec<_>];
+    assert!(v.is_empty());
+
+    let sorted = 7u8;
+    let v: Vec<u32> = collect![sorted as u32];
+    assert_eq!(v, [7]);
+}
+
+#[test]
+fn test_sorted_by() {
+    let v = collect![sorted_by (|a: &i32, b: &i32| b.cmp(a)) as Vec<_>: 3, 1, 2];
+    assert_eq!(v, [3, 2, 1]);
+
+    // The sort is stable.
+    let v = collect![
+        sorted_by (|a: &(i32, char), b: &(i32, char)| a.0.cmp(&b.0)) as Vec<_>:
+        (2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')
+    ];
+    assert_eq!(v, [(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+}
+
+#[derive(Debug, PartialEq)]
+struct Task {
+    priority: u8,
+    name: &'static str,
+}
+
+#[test]
+fn test_sort_by_key() {
+    let task = |priority, name| Task { priority, name };
+    let v = collect![sort_by_key (|x| x.priority) as Vec<_>:
+        task(2, "b"), task(1, "a"), task(2, "c"), task(0, "z"), task(1, "d")];
+
+    // Tasks with equal priorities keep their original order.
+    let names: Vec<_> = v.iter().map(|t| t.name).collect();
+    assert_eq!(names, ["z", "a", "d", "b", "c"]);
+
+    let v = collect![sort_by_key (|x: &i32| -x) as Vec<_>: 1, ..vec![3, 2]];
+    assert_eq!(v, [3, 2, 1]);
+}
+
+#[test]
+fn test_ring() {
+    let d = collect![ring 16 as VecDeque<_>: 1, 2, 3];
+    assert!(d.capacity() >= 16);
+    assert_eq!(d, [1, 2, 3]);
+
+    let d = collect![ring 2 as VecDeque<_>: 1, 2, 3, ..4..6];
+    assert!(d.capacity() >= 5);
+    assert_eq!(d, [1, 2, 3, 4, 5]);
+
+    let n = 8;
+    let d: VecDeque<i32> = collect![ring n as VecDeque<_>];
+    assert!(d.capacity() >= 8);
+    assert!(d.is_empty());
+
+    let xs = [4, 5];
+    let d = collect![ring (n * 4) cloned as VecDeque<i32>: ..&xs];
+    assert!(d.capacity() >= 32);
+    assert_eq!(d, [4, 5]);
+
+    let v = collect![sorted ring 8 as Vec<_>: 3, 1, 2];
+    assert!(v.capacity() >= 8);
+    assert_eq!(v, [1, 2, 3]);
+}
+
+#[test]
+fn test_outline() {
+    let v = collect![outline as Vec<_>: 1, 2, 3];
+    assert_eq!(v, collect![as Vec<_>: 1, 2, 3]);
+
+    let m = collect![outline as HashMap<_, _>: "a" => 1, "b" => 2,];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 1, "b" => 2]);
+
+    let s = collect![outline as String: 'x'];
+    assert_eq!(s, "x");
+
+    let v = collect![sorted outline as Vec<_>: 3, 1, 2];
+    assert_eq!(v, [1, 2, 3]);
+}
+
+#[test]
+fn test_reserve() {
+    let v = collect![reserve(32) as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+    assert!(v.capacity() >= 35);
+
+    let extra = 4;
+    let s = collect![reserve extra as String: "ab", ..vec!["cd"]];
+    assert_eq!(s, "abcd");
+    assert!(s.capacity() >= 6);
+
+    let m = collect![reserve 10 as HashMap<_, _>: 1 => 'a'];
+    assert!(m.capacity() >= 11);
+
+    let v: Vec<u8> = collect![reserve 8 as Vec<_>];
+    assert!(v.is_empty());
+    assert!(v.capacity() >= 8);
+}
+
+#[test]
+fn test_pow2() {
+    fn check(v: Vec<u32>) {
+        assert!(v.capacity().is_power_of_two());
+        assert_eq!(v.capacity(), v.len().next_power_of_two());
+    }
+
+    check(collect![pow2 as Vec<_>: 1]);
+    check(collect![pow2 as Vec<_>: 1, 2]);
+    check(collect![pow2 as Vec<_>: 1, 2, 3]);
+    check(collect![pow2 as Vec<_>: 1, 2, 3, 4, 5]);
+    check(collect![pow2 as Vec<_>: 1, 2, 3, 4, 5, 6, 7, 8]);
+    check(collect![pow2 as Vec<_>: 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    check(collect![pow2 as Vec<_>: 1, ..0..10]);
+
+    // Nothing to store means nothing to round up.
+    let v = collect![pow2 as Vec<i32>];
+    assert_eq!(v.capacity(), 0);
+}
+
+#[test]
+fn test_headroom() {
+    for n in 1..=64u32 {
+        // If the set had to grow during insertion, it would end up with more capacity than it started with.
+        let s = collect![headroom as HashSet<_>: ..0..n];
+        assert_eq!(s.len(), n as usize);
+        let wanted = n as usize * 8 / 7 + 1;
+        assert_eq!(s.capacity(), HashSet::<u32>::with_capacity(wanted).capacity());
+
+        // The same holds without the modifier.
+        let s = collect![as HashSet<_>: ..0..n];
+        assert_eq!(s.capacity(), HashSet::<u32>::with_capacity(n as usize).capacity());
+    }
+
+    let m = collect![headroom as HashMap<_, _>: 1 => 'a', 2 => 'b'];
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn test_pad_to() {
+    let v = collect![pad_to 5 with 0 as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3, 0, 0]);
+    assert_eq!(v.capacity(), 5);
+
+    let v = collect![pad_to 3 with 0 as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+
+    // Longer collections are left alone.
+    let v = collect![pad_to 2 with 0 as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+
+    let d = collect![pad_to 4 with '.' as VecDeque<_>: 'a', 'b'];
+    assert_eq!(d, ['a', 'b', '.', '.']);
+
+    let v = collect![pad_to 4 with (String::from("-")) sorted as Vec<_>: String::from("b"), String::from("a")];
+    assert_eq!(v, ["a", "b", "-", "-"]);
+
+    let v: Vec<i32> = collect![pad_to 2 with 9 as Vec<_>];
+    assert_eq!(v, [9, 9]);
+}
+
+#[test]
+fn test_terminate_with() {
+    let v = collect![terminate_with 0 as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3, 0]);
+    assert_eq!(v.capacity(), 4);
+
+    let v: Vec<i32> = collect![terminate_with 0:];
+    assert_eq!(v, [0]);
+
+    let d = collect![terminate_with '\0' rev as VecDeque<_>: 'a', ..vec!['b', 'c']];
+    assert_eq!(d, ['c', 'b', 'a', '\0']);
+}
+
+#[test]
+fn test_check() {
+    let v = collect![check (|c: &Vec<_>| c.len() <= 10) as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+
+    // The check sees the collection after the other modifiers have been applied.
+    let v = collect![check (|c: &Vec<i32>| c.windows(2).all(|w| w[0] <= w[1])) sorted as Vec<_>: 3, 1, 2];
+    assert_eq!(v, [1, 2, 3]);
+}
+
+#[test]
+#[should_panic(expected = "collect![check]: collection failed its check")]
+fn test_check_fails() {
+    let _ = collect![check (|c: &HashSet<i32>| c.len() == 3) as HashSet<_>: 1, 2, 2];
+}
+
+#[test]
+fn test_try_check() {
+    let r = collect![try_check (|c: &Vec<i32>| !c.is_empty()): 1, 2];
+    assert_eq!(r, Ok(vec![1, 2]));
+
+    let r = collect![try_check (|c: &Vec<i32>| !c.is_empty()):];
+    assert_eq!(r, Err(CheckError));
+
+    let r = collect![try_check (|m: &HashMap<_, _>| m.contains_key("host")) as HashMap<&str, &str>: "port" => "80"];
+    assert!(r.is_err());
+}
+
+#[test]
+fn test_rev() {
+    let v = collect![rev as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [3, 2, 1]);
+
+    let d = collect![rev as VecDeque<_>: 'a', ..vec!['b', 'c']];
+    assert_eq!(d, ['c', 'b', 'a']);
+
+    // Reversed by `char`, so multi-byte characters survive intact.
+    let s = collect![rev as String: "añ", "€"];
+    assert_eq!(s, "€ña");
+
+    let v = collect![rev sorted as Vec<_>: 2, 3, 1];
+    assert_eq!(v, [3, 2, 1]);
+
+    let v: Vec<i32> = collect![rev as Vec<_>];
+    assert!(v.is_empty());
+
+    // A variable that happens to be called `rev`, cast to a primitive, is just an element.
+    let rev = 3u8;
+    let v: Vec<u16> = collect![rev as u16];
+    assert_eq!(v, [3]);
+}
+
+#[test]
+fn test_shrink() {
+    let v = collect![shrink as Vec<_>: 1, 2, 3];
+    assert_eq!(v.capacity(), v.len());
+
+    let v = collect![shrink pow2 as Vec<_>: 1, 2, 3, 4, 5];
+    assert_eq!(v, [1, 2, 3, 4, 5]);
+    assert_eq!(v.capacity(), v.len());
+
+    let v = collect![shrink reserve 100 as Vec<_>: 'a', ..vec!['b']];
+    assert_eq!(v.capacity(), v.len());
+
+    let s = collect![shrink as String: "c", "ab"];
+    assert_eq!(s.capacity(), s.len());
+
+    let v = collect![shrink sorted as Vec<_>: 3, 1, 2];
+    assert_eq!(v, [1, 2, 3]);
+
+    let shrink = 0.5f32;
+    let v: Vec<f64> = collect![shrink as f64];
+    assert_eq!(v, [0.5]);
+}
+
+#[test]
+fn test_with_hash() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    let (v, hash) = collect![with_hash as Vec<u8>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+
+    // Each element is hashed in turn, as it's inserted.
+    let mut hasher = DefaultHasher::new();
+    for x in &v {
+        x.hash(&mut hasher);
+    }
+    assert_eq!(hash, hasher.finish());
+
+    let (_, same) = collect![with_hash as Vec<u8>: 1, ..vec![2, 3]];
+    assert_eq!(same, hash);
+    let (_, different) = collect![with_hash as Vec<u8>: 3, 2, 1];
+    assert_ne!(different, hash);
+
+    // Duplicates still count, even though the set drops them.
+    let (s, dup) = collect![with_hash as HashSet<u8>: 1, 2, 3, 3];
+    assert_eq!(s.len(), 3);
+    assert_ne!(dup, hash);
+
+    let (v, empty): (Vec<u8>, _) = collect![with_hash as Vec<_>];
+    assert!(v.is_empty());
+    assert_eq!(empty, DefaultHasher::new().finish());
+}
+
+/// An iterator which never reports how long it is.
+struct Unsized<I>(I);
+
+impl<I: Iterator> Iterator for Unsized<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.0.next()
+    }
+}
+
+#[test]
+fn test_timed() {
+    use std::time::{Duration, Instant};
+
+    let outer = Instant::now();
+    let (v, elapsed) = collect![timed as Vec<_>: 1, ..2..1000];
+    let total = outer.elapsed();
+    assert_eq!(v, (1..1000).collect::<Vec<_>>());
+    assert!(elapsed >= Duration::from_secs(0));
+    assert!(elapsed <= total);
+
+    let ((s, hash), _) = collect![timed with_hash as Vec<_>: 1, 2, 3];
+    assert_eq!(s, [1, 2, 3]);
+    assert_eq!(hash, collect![with_hash as Vec<_>: 1, 2, 3].1);
+
+    let timed = 200u8;
+    let v: Vec<i16> = collect![timed as i16];
+    assert_eq!(v, [200]);
+}
+
+#[test]
+fn test_cap_from() {
+    let expected_len = 10;
+    let src = Unsized(0..10);
+    assert_eq!(src.size_hint(), (0, None));
+
+    // Growing from nothing would overshoot to a larger power of two.
+    let v = collect![cap_from(expected_len) as Vec<_>: ..src];
+    assert_eq!(v.len(), 10);
+    assert_eq!(v.capacity(), 10);
+
+    // Too small is fine; it just grows.
+    let v = collect![cap_from 1 as Vec<_>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+
+    let v = collect![cap_from 16 cloned as Vec<i32>: ..&[1, 2]];
+    assert_eq!(v.capacity(), 16);
+}
+
+#[test]
+fn test_expected() {
+    let s = collect![as HashSet<_>: expected(5); ..(0..1000).map(|i| i % 5)];
+    assert_eq!(s, collect![0, 1, 2, 3, 4]);
+    assert!(s.capacity() >= 5);
+    assert!(s.capacity() < 1000);
+
+    // It's only a hint; more distinct elements still fit.
+    let m = collect![as HashMap<_, _>: expected(1); 0 => 'a', ..vec![(1, 'b'), (2, 'c')]];
+    assert_eq!(m, collect![0 => 'a', 1 => 'b', 2 => 'c']);
+
+    let s: HashSet<i32> = collect![as HashSet<_>: expected(8);];
+    assert!(s.capacity() >= 8);
+}
+
+#[test]
+fn test_dedup_adjacent() {
+    let v = collect![dedup_adjacent: 1, 1, 2, 3, 3, 1];
+    assert_eq!(v, vec![1, 2, 3, 1]);
+
+    let v = collect![dedup_adjacent as Vec<_>: "a", "a", "a", ..vec!["b", "a"]];
+    assert_eq!(v, vec!["a", "b", "a"]);
+
+    let v = collect![dedup_adjacent sorted as Vec<_>: 3, 1, 3, 2, 1];
+    assert_eq!(v, vec![1, 2, 3]);
+
+    let v: Vec<i32> = collect![dedup_adjacent:];
+    assert!(v.is_empty());
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Item {
+    id: u32,
+    name: &'static str,
+}
+
+#[test]
+fn test_dedup_by_key() {
+    let item = |id, name| Item { id, name };
+    let v = collect![dedup_by_key (|x| x.id):
+        item(2, "a"), item(1, "b"), item(2, "c"), item(3, "d"), item(1, "e")];
+    assert_eq!(v, vec![item(2, "a"), item(1, "b"), item(3, "d")]);
+
+    let extra = vec![item(3, "x"), item(4, "y")];
+    let v = collect![dedup_by_key (|x| x.id) sorted_by (|a: &Item, b: &Item| a.id.cmp(&b.id)) as Vec<_>:
+        item(3, "d"), ..extra];
+    assert_eq!(v, vec![item(3, "d"), item(4, "y")]);
+
+    let v = collect![dedup_by_key (|x: &Item| x.id):];
+    assert!(v.is_empty());
+}
+
+#[test]
+fn test_rotate() {
+    let d = collect![rotate 2 as VecDeque<_>: 1, 2, 3, 4, 5];
+    assert_eq!(d, collect![as VecDeque<_>: 3, 4, 5, 1, 2]);
+    assert!(d.capacity() >= 5);
+
+    // Rotations wrap around.
+    let d = collect![rotate 7 as VecDeque<_>: 1, 2, 3, 4, 5];
+    assert_eq!(d, collect![as VecDeque<_>: 3, 4, 5, 1, 2]);
+
+    let v = collect![rotate (1 + 2) as Vec<_>: 'a', 'b', 'c', 'd'];
+    assert_eq!(v, vec!['d', 'a', 'b', 'c']);
+
+    let d: VecDeque<i32> = collect![rotate 3 as VecDeque<_>];
+    assert!(d.is_empty());
+}
+
+/// A collection which can *only* be built with `FromIterator`.
+#[derive(Debug, PartialEq)]
+struct Frozen<T> {
+    items: Vec<T>,
+    hint: usize,
+}
+
+impl<T> std::iter::FromIterator<T> for Frozen<T> {
+    fn from_iter<I: IntoIterator<Item=T>>(iter: I) -> Self {
+        let iter = iter.into_iter();
+        let hint = iter.size_hint().0;
+        Frozen { items: iter.collect(), hint }
+    }
+}
+
+#[test]
+fn test_via_from_iter() {
+    let f = collect![via_from_iter as Frozen<_>: 1, 2, 3];
+    assert_eq!(f, Frozen { items: vec![1, 2, 3], hint: 3 });
+
+    let f = collect![via_from_iter as Frozen<_>: "a" => 1, "b" => 2,];
+    assert_eq!(f, Frozen { items: vec![("a", 1), ("b", 2)], hint: 2 });
+
+    let f: Frozen<i32> = collect![via_from_iter as Frozen<_>];
+    assert_eq!(f, Frozen { items: vec![], hint: 0 });
+}
+
+thread_local! {
+    static EVENTS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+fn event(e: String) {
+    EVENTS.with(|es| es.borrow_mut().push(e));
+}
+
+/// A sequence which logs when it is allocated.
+struct LoggedVec(Vec<i32>);
+
+impl LoggedVec {
+    fn with_capacity(n: usize) -> LoggedVec {
+        event(format!("alloc {}", n));
+        LoggedVec(Vec::with_capacity(n))
+    }
+}
+
+impl Extend<i32> for LoggedVec {
+    fn extend<I: IntoIterator<Item=i32>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[test]
+fn test_prealloc() {
+    let expensive = |x: i32| { event(format!("compute {}", x)); x };
+
+    let v = collect![prealloc as LoggedVec: expensive(1), expensive(2), expensive(3)];
+    assert_eq!(v.0, [1, 2, 3]);
+    assert_eq!(EVENTS.with(|es| es.borrow().clone()), ["alloc 3", "compute 1", "compute 2", "compute 3"]);
+
+    let v = collect![prealloc as Vec<_>: 1, 2, 3];
+    assert!(v.capacity() >= 3);
+
+    let m = collect![prealloc as HashMap<_, _>: "a" => 1, "b" => 2,];
+    assert!(m.capacity() >= 2);
+    assert_eq!(m["b"], 2);
+}
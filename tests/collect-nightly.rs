@@ -0,0 +1,53 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that the forms behind the `nightly` feature work.  It only builds with `--features nightly` on a nightly compiler.
+*/
+#![cfg(feature = "nightly")]
+#![feature(allocator_api)]
+
+#[macro_use] extern crate collect_mac;
+
+use std::alloc::{AllocError, Allocator, Global, Layout};
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::ptr::NonNull;
+
+/// Forwards to the global allocator, counting how many allocations it makes.
+#[derive(Default)]
+struct Counting {
+    allocs: Cell<usize>,
+}
+
+unsafe impl Allocator for &Counting {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.allocs.set(self.allocs.get() + 1);
+        Global.allocate(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        Global.deallocate(ptr, layout)
+    }
+}
+
+#[test]
+fn test_in_alloc() {
+    let alloc = Counting::default();
+    let v = collect![in (&alloc) as Vec<_, &Counting>: 1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+    assert_eq!(v.capacity(), 3);
+    assert_eq!(alloc.allocs.get(), 1);
+
+    let d = collect![in Global as VecDeque<_, Global>: "a", "b",];
+    assert_eq!(d, ["a", "b"]);
+
+    let v: Vec<i32, Global> = collect![in Global as Vec<_, Global>];
+    assert!(v.is_empty());
+}
@@ -0,0 +1,335 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that spreads (`..EXPR` elements) and the modifiers which act on them work as advertised.
+*/
+
+#[macro_use] extern crate collect_mac;
+
+use std::collections::{BTreeMap, HashMap};
+
+#[test]
+fn test_spread_seq() {
+    let xs = vec![1, 2, 3];
+    let v: Vec<i32> = collect![..xs];
+    assert_eq!(v, [1, 2, 3]);
+
+    let v: Vec<i32> = collect![as Vec<_>: ..0..2, 5, 6, 7, 8, 9, ..vec![10], 11];
+    assert_eq!(v, [0, 1, 5, 6, 7, 8, 9, 10, 11]);
+
+    let v: Vec<i32> = collect![-1, ..Vec::new(), -2,];
+    assert_eq!(v, [-1, -2]);
+
+    let s: String = collect!['a', ..vec!['b', 'c'], 'd'];
+    assert_eq!(s, "abcd");
+}
+
+#[test]
+fn test_spread_map() {
+    let base = collect![as BTreeMap<_, _>: 1 => "one", 2 => "two"];
+    let m = collect![as BTreeMap<_, _>: 0 => "zero", ..base, 2 => "deux"];
+    assert_eq!(m, collect![0 => "zero", 1 => "one", 2 => "deux"]);
+
+    let m: HashMap<&str, i32> = collect!["a" => 1, "b" => 2, "c" => 3, ..vec![("d", 4)]];
+    assert_eq!(m.len(), 4);
+}
+
+#[test]
+fn test_spread_single_alloc() {
+    let xs = vec![1, 2, 3, 4, 5];
+    let v = collect![as Vec<i32>: 0, ..xs, 6];
+    assert_eq!(v, [0, 1, 2, 3, 4, 5, 6]);
+    assert_eq!(v.capacity(), 7);
+}
+
+/// A map which records the size hint of every call to `extend`, standing in for an allocation counter.
+#[derive(Default)]
+struct HintMap {
+    inner: HashMap<i32, i32>,
+    hints: Vec<(usize, Option<usize>)>,
+}
+
+impl Extend<(i32, i32)> for HintMap {
+    fn extend<I: IntoIterator<Item=(i32, i32)>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.hints.push(iter.size_hint());
+        self.inner.extend(iter);
+    }
+}
+
+#[test]
+fn test_spread_map_single_alloc() {
+    let pairs = (0..10).map(|i| (i, i * i));
+    let m = collect![as HashMap<_, _>: ..pairs];
+    assert_eq!(m.len(), 10);
+    // A map which grew while being built would have re-hashed into a larger table.
+    assert_eq!(m.capacity(), HashMap::<i32, i32>::with_capacity(10).capacity());
+
+    // The spread's exact size reaches the map in a single call to `extend`, along with any literal entries.
+    let m = collect![as HintMap: ..(0..10).map(|i| (i, i * i))];
+    assert_eq!(m.hints, [(10, Some(10))]);
+    assert_eq!(m.inner[&9], 81);
+
+    let m = collect![as HintMap: -1 => 1, ..(0..10).map(|i| (i, i)), 10 => 10];
+    assert_eq!(m.hints, [(12, Some(12))]);
+    assert_eq!(m.inner.len(), 12);
+}
+
+#[test]
+fn test_cloned_tuple_slice() {
+    let pairs: &[(i32, &str)] = &[(1, "a"), (2, "b"), (3, "c")];
+    let m = collect![cloned as HashMap<_, _>: ..pairs];
+    assert_eq!(m, collect![as HashMap<_, _>: 1 => "a", 2 => "b", 3 => "c"]);
+    assert_eq!(m.capacity(), HashMap::<i32, &str>::with_capacity(3).capacity());
+
+    let owned = vec![(String::from("k"), vec![1])];
+    let m = collect![cloned as BTreeMap<_, _>: ..&owned, String::from("j") => vec![]];
+    assert_eq!(m[&String::from("k")], [1]);
+    assert_eq!(owned.len(), 1);
+
+    let m = collect![cloned as HintMap: ..&[(1, 1), (2, 4)][..]];
+    assert_eq!(m.hints, [(2, Some(2))]);
+}
+
+#[test]
+fn test_long_literal_list() {
+    // Long enough that munching even a few elements at a time would overflow the default recursion limit.
+    let v: Vec<i32> = collect![
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19,
+        20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39,
+        40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59,
+        60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79,
+        80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99,
+        100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119,
+        120, 121, 122, 123, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139,
+        140, 141, 142, 143, 144, 145, 146, 147, 148, 149, 150, 151, 152, 153, 154, 155, 156, 157, 158, 159,
+        160, 161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174, 175, 176, 177, 178, 179,
+        180, 181, 182, 183, 184, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199,
+        200, 201, 202, 203, 204, 205, 206, 207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219,
+        220, 221, 222, 223, 224, 225, 226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239,
+        240, 241, 242, 243, 244, 245, 246, 247, 248, 249, 250, 251, 252, 253, 254, 255, 256, 257, 258, 259,
+        260, 261, 262, 263, 264, 265, 266, 267, 268, 269, 270, 271, 272, 273, 274, 275, 276, 277, 278, 279,
+        280, 281, 282, 283, 284, 285, 286, 287, 288, 289, 290, 291, 292, 293, 294, 295, 296, 297, 298, 299,
+        300, 301, 302, 303, 304, 305, 306, 307, 308, 309, 310, 311, 312, 313, 314, 315, 316, 317, 318, 319,
+        320, 321, 322, 323, 324, 325, 326, 327, 328, 329, 330, 331, 332, 333, 334, 335, 336, 337, 338, 339,
+        340, 341, 342, 343, 344, 345, 346, 347, 348, 349, 350, 351, 352, 353, 354, 355, 356, 357, 358, 359,
+        360, 361, 362, 363, 364, 365, 366, 367, 368, 369, 370, 371, 372, 373, 374, 375, 376, 377, 378, 379,
+        380, 381, 382, 383, 384, 385, 386, 387, 388, 389, 390, 391, 392, 393, 394, 395, 396, 397, 398, 399,
+        400, 401, 402, 403, 404, 405, 406, 407, 408, 409, 410, 411, 412, 413, 414, 415, 416, 417, 418, 419,
+        420, 421, 422, 423, 424, 425, 426, 427, 428, 429, 430, 431, 432, 433, 434, 435, 436, 437, 438, 439,
+        440, 441, 442, 443, 444, 445, 446, 447, 448, 449, 450, 451, 452, 453, 454, 455, 456, 457, 458, 459,
+        460, 461, 462, 463, 464, 465, 466, 467, 468, 469, 470, 471, 472, 473, 474, 475, 476, 477, 478, 479,
+        480, 481, 482, 483, 484, 485, 486, 487, 488, 489, 490, 491, 492, 493, 494, 495, 496, 497, 498, 499,
+        500, 501, 502, 503, 504, 505, 506, 507, 508, 509, 510, 511, 512, 513, 514, 515, 516, 517, 518, 519,
+        520, 521, 522, 523, 524, 525, 526, 527, 528, 529, 530, 531, 532, 533, 534, 535, 536, 537, 538, 539,
+        540, 541, 542, 543, 544, 545, 546, 547, 548, 549, 550, 551, 552, 553, 554, 555, 556, 557, 558, 559,
+        560, 561, 562, 563, 564, 565, 566, 567, 568, 569, 570, 571, 572, 573, 574, 575, 576, 577, 578, 579,
+        580, 581, 582, 583, 584, 585, 586, 587, 588, 589, 590, 591, 592, 593, 594, 595, 596, 597, 598, 599,
+        600, 601, 602, 603, 604, 605, 606, 607, 608, 609, 610, 611, 612, 613, 614, 615, 616, 617, 618, 619,
+        620, 621, 622, 623, 624, 625, 626, 627, 628, 629, 630, 631, 632, 633, 634, 635, 636, 637, 638, 639,
+        640, 641, 642, 643, 644, 645, 646, 647, 648, 649, 650, 651, 652, 653, 654, 655, 656, 657, 658, 659,
+        660, 661, 662, 663, 664, 665, 666, 667, 668, 669, 670, 671, 672, 673, 674, 675, 676, 677, 678, 679,
+        680, 681, 682, 683, 684, 685, 686, 687, 688, 689, 690, 691, 692, 693, 694, 695, 696, 697, 698, 699,
+        700, 701, 702, 703, 704, 705, 706, 707, 708, 709, 710, 711, 712, 713, 714, 715, 716, 717, 718, 719,
+        720, 721, 722, 723, 724, 725, 726, 727, 728, 729, 730, 731, 732, 733, 734, 735, 736, 737, 738, 739,
+        740, 741, 742, 743, 744, 745, 746, 747, 748, 749, 750, 751, 752, 753, 754, 755, 756, 757, 758, 759,
+        760, 761, 762, 763, 764, 765, 766, 767, 768, 769, 770, 771, 772, 773, 774, 775, 776, 777, 778, 779,
+        780, 781, 782, 783, 784, 785, 786, 787, 788, 789, 790, 791, 792, 793, 794, 795, 796, 797, 798, 799,
+        800, 801, 802, 803, 804, 805, 806, 807, 808, 809, 810, 811, 812, 813, 814, 815, 816, 817, 818, 819,
+        820, 821, 822, 823, 824, 825, 826, 827, 828, 829, 830, 831, 832, 833, 834, 835, 836, 837, 838, 839,
+        840, 841, 842, 843, 844, 845, 846, 847, 848, 849, 850, 851, 852, 853, 854, 855, 856, 857, 858, 859,
+        860, 861, 862, 863, 864, 865, 866, 867, 868, 869, 870, 871, 872, 873, 874, 875, 876, 877, 878, 879,
+        880, 881, 882, 883, 884, 885, 886, 887, 888, 889, 890, 891, 892, 893, 894, 895, 896, 897, 898, 899,
+        900, 901, 902, 903, 904, 905, 906, 907, 908, 909, 910, 911, 912, 913, 914, 915, 916, 917, 918, 919,
+        920, 921, 922, 923, 924, 925, 926, 927, 928, 929, 930, 931, 932, 933, 934, 935, 936, 937, 938, 939,
+        940, 941, 942, 943, 944, 945, 946, 947, 948, 949, 950, 951, 952, 953, 954, 955, 956, 957, 958, 959,
+        960, 961, 962, 963, 964, 965, 966, 967, 968, 969, 970, 971, 972, 973, 974, 975, 976, 977, 978, 979,
+        980, 981, 982, 983, 984, 985, 986, 987, 988, 989, 990, 991, 992, 993, 994, 995, 996, 997, 998, 999,
+    ];
+    assert_eq!(v, (0..1000).collect::<Vec<_>>());
+    assert_eq!(v.capacity(), 1000);
+}
+
+#[test]
+fn test_long_literal_map() {
+    let m: BTreeMap<i32, i32> = collect![
+        0 => 0, 1 => 1, 2 => 4, 3 => 9, 4 => 16, 5 => 25, 6 => 36, 7 => 49, 8 => 64, 9 => 81,
+        10 => 100, 11 => 121, 12 => 144, 13 => 169, 14 => 196, 15 => 225, 16 => 256, 17 => 289, 18 => 324, 19 => 361,
+        20 => 400, 21 => 441, 22 => 484, 23 => 529, 24 => 576, 25 => 625, 26 => 676, 27 => 729, 28 => 784, 29 => 841,
+        30 => 900, 31 => 961, 32 => 1024, 33 => 1089, 34 => 1156, 35 => 1225, 36 => 1296, 37 => 1369, 38 => 1444, 39 => 1521,
+        40 => 1600, 41 => 1681, 42 => 1764, 43 => 1849, 44 => 1936, 45 => 2025, 46 => 2116, 47 => 2209, 48 => 2304, 49 => 2401,
+        50 => 2500, 51 => 2601, 52 => 2704, 53 => 2809, 54 => 2916, 55 => 3025, 56 => 3136, 57 => 3249, 58 => 3364, 59 => 3481,
+        60 => 3600, 61 => 3721, 62 => 3844, 63 => 3969, 64 => 4096, 65 => 4225, 66 => 4356, 67 => 4489, 68 => 4624, 69 => 4761,
+        70 => 4900, 71 => 5041, 72 => 5184, 73 => 5329, 74 => 5476, 75 => 5625, 76 => 5776, 77 => 5929, 78 => 6084, 79 => 6241,
+        80 => 6400, 81 => 6561, 82 => 6724, 83 => 6889, 84 => 7056, 85 => 7225, 86 => 7396, 87 => 7569, 88 => 7744, 89 => 7921,
+        90 => 8100, 91 => 8281, 92 => 8464, 93 => 8649, 94 => 8836, 95 => 9025, 96 => 9216, 97 => 9409, 98 => 9604, 99 => 9801,
+        100 => 10000, 101 => 10201, 102 => 10404, 103 => 10609, 104 => 10816, 105 => 11025, 106 => 11236, 107 => 11449, 108 => 11664, 109 => 11881,
+        110 => 12100, 111 => 12321, 112 => 12544, 113 => 12769, 114 => 12996, 115 => 13225, 116 => 13456, 117 => 13689, 118 => 13924, 119 => 14161,
+        120 => 14400, 121 => 14641, 122 => 14884, 123 => 15129, 124 => 15376, 125 => 15625, 126 => 15876, 127 => 16129, 128 => 16384, 129 => 16641,
+        130 => 16900, 131 => 17161, 132 => 17424, 133 => 17689, 134 => 17956, 135 => 18225, 136 => 18496, 137 => 18769, 138 => 19044, 139 => 19321,
+        140 => 19600, 141 => 19881, 142 => 20164, 143 => 20449, 144 => 20736, 145 => 21025, 146 => 21316, 147 => 21609, 148 => 21904, 149 => 22201,
+        150 => 22500, 151 => 22801, 152 => 23104, 153 => 23409, 154 => 23716, 155 => 24025, 156 => 24336, 157 => 24649, 158 => 24964, 159 => 25281,
+        160 => 25600, 161 => 25921, 162 => 26244, 163 => 26569, 164 => 26896, 165 => 27225, 166 => 27556, 167 => 27889, 168 => 28224, 169 => 28561,
+        170 => 28900, 171 => 29241, 172 => 29584, 173 => 29929, 174 => 30276, 175 => 30625, 176 => 30976, 177 => 31329, 178 => 31684, 179 => 32041,
+        180 => 32400, 181 => 32761, 182 => 33124, 183 => 33489, 184 => 33856, 185 => 34225, 186 => 34596, 187 => 34969, 188 => 35344, 189 => 35721,
+        190 => 36100, 191 => 36481, 192 => 36864, 193 => 37249, 194 => 37636, 195 => 38025, 196 => 38416, 197 => 38809, 198 => 39204, 199 => 39601,
+        200 => 40000, 201 => 40401, 202 => 40804, 203 => 41209, 204 => 41616, 205 => 42025, 206 => 42436, 207 => 42849, 208 => 43264, 209 => 43681,
+        210 => 44100, 211 => 44521, 212 => 44944, 213 => 45369, 214 => 45796, 215 => 46225, 216 => 46656, 217 => 47089, 218 => 47524, 219 => 47961,
+        220 => 48400, 221 => 48841, 222 => 49284, 223 => 49729, 224 => 50176, 225 => 50625, 226 => 51076, 227 => 51529, 228 => 51984, 229 => 52441,
+        230 => 52900, 231 => 53361, 232 => 53824, 233 => 54289, 234 => 54756, 235 => 55225, 236 => 55696, 237 => 56169, 238 => 56644, 239 => 57121,
+        240 => 57600, 241 => 58081, 242 => 58564, 243 => 59049, 244 => 59536, 245 => 60025, 246 => 60516, 247 => 61009, 248 => 61504, 249 => 62001,
+        250 => 62500, 251 => 63001, 252 => 63504, 253 => 64009, 254 => 64516, 255 => 65025, 256 => 65536, 257 => 66049, 258 => 66564, 259 => 67081,
+        260 => 67600, 261 => 68121, 262 => 68644, 263 => 69169, 264 => 69696, 265 => 70225, 266 => 70756, 267 => 71289, 268 => 71824, 269 => 72361,
+        270 => 72900, 271 => 73441, 272 => 73984, 273 => 74529, 274 => 75076, 275 => 75625, 276 => 76176, 277 => 76729, 278 => 77284, 279 => 77841,
+        280 => 78400, 281 => 78961, 282 => 79524, 283 => 80089, 284 => 80656, 285 => 81225, 286 => 81796, 287 => 82369, 288 => 82944, 289 => 83521,
+        290 => 84100, 291 => 84681, 292 => 85264, 293 => 85849, 294 => 86436, 295 => 87025, 296 => 87616, 297 => 88209, 298 => 88804, 299 => 89401,
+        300 => 90000, 301 => 90601, 302 => 91204, 303 => 91809, 304 => 92416, 305 => 93025, 306 => 93636, 307 => 94249, 308 => 94864, 309 => 95481,
+        310 => 96100, 311 => 96721, 312 => 97344, 313 => 97969, 314 => 98596, 315 => 99225, 316 => 99856, 317 => 100489, 318 => 101124, 319 => 101761,
+        320 => 102400, 321 => 103041, 322 => 103684, 323 => 104329, 324 => 104976, 325 => 105625, 326 => 106276, 327 => 106929, 328 => 107584, 329 => 108241,
+        330 => 108900, 331 => 109561, 332 => 110224, 333 => 110889, 334 => 111556, 335 => 112225, 336 => 112896, 337 => 113569, 338 => 114244, 339 => 114921,
+        340 => 115600, 341 => 116281, 342 => 116964, 343 => 117649, 344 => 118336, 345 => 119025, 346 => 119716, 347 => 120409, 348 => 121104, 349 => 121801,
+        350 => 122500, 351 => 123201, 352 => 123904, 353 => 124609, 354 => 125316, 355 => 126025, 356 => 126736, 357 => 127449, 358 => 128164, 359 => 128881,
+        360 => 129600, 361 => 130321, 362 => 131044, 363 => 131769, 364 => 132496, 365 => 133225, 366 => 133956, 367 => 134689, 368 => 135424, 369 => 136161,
+        370 => 136900, 371 => 137641, 372 => 138384, 373 => 139129, 374 => 139876, 375 => 140625, 376 => 141376, 377 => 142129, 378 => 142884, 379 => 143641,
+        380 => 144400, 381 => 145161, 382 => 145924, 383 => 146689, 384 => 147456, 385 => 148225, 386 => 148996, 387 => 149769, 388 => 150544, 389 => 151321,
+        390 => 152100, 391 => 152881, 392 => 153664, 393 => 154449, 394 => 155236, 395 => 156025, 396 => 156816, 397 => 157609, 398 => 158404, 399 => 159201,
+        400 => 160000, 401 => 160801, 402 => 161604, 403 => 162409, 404 => 163216, 405 => 164025, 406 => 164836, 407 => 165649, 408 => 166464, 409 => 167281,
+        410 => 168100, 411 => 168921, 412 => 169744, 413 => 170569, 414 => 171396, 415 => 172225, 416 => 173056, 417 => 173889, 418 => 174724, 419 => 175561,
+        420 => 176400, 421 => 177241, 422 => 178084, 423 => 178929, 424 => 179776, 425 => 180625, 426 => 181476, 427 => 182329, 428 => 183184, 429 => 184041,
+        430 => 184900, 431 => 185761, 432 => 186624, 433 => 187489, 434 => 188356, 435 => 189225, 436 => 190096, 437 => 190969, 438 => 191844, 439 => 192721,
+        440 => 193600, 441 => 194481, 442 => 195364, 443 => 196249, 444 => 197136, 445 => 198025, 446 => 198916, 447 => 199809, 448 => 200704, 449 => 201601,
+        450 => 202500, 451 => 203401, 452 => 204304, 453 => 205209, 454 => 206116, 455 => 207025, 456 => 207936, 457 => 208849, 458 => 209764, 459 => 210681,
+        460 => 211600, 461 => 212521, 462 => 213444, 463 => 214369, 464 => 215296, 465 => 216225, 466 => 217156, 467 => 218089, 468 => 219024, 469 => 219961,
+        470 => 220900, 471 => 221841, 472 => 222784, 473 => 223729, 474 => 224676, 475 => 225625, 476 => 226576, 477 => 227529, 478 => 228484, 479 => 229441,
+        480 => 230400, 481 => 231361, 482 => 232324, 483 => 233289, 484 => 234256, 485 => 235225, 486 => 236196, 487 => 237169, 488 => 238144, 489 => 239121,
+        490 => 240100, 491 => 241081, 492 => 242064, 493 => 243049, 494 => 244036, 495 => 245025, 496 => 246016, 497 => 247009, 498 => 248004, 499 => 249001,
+        500 => 250000, 501 => 251001, 502 => 252004, 503 => 253009, 504 => 254016, 505 => 255025, 506 => 256036, 507 => 257049, 508 => 258064, 509 => 259081,
+        510 => 260100, 511 => 261121, 512 => 262144, 513 => 263169, 514 => 264196, 515 => 265225, 516 => 266256, 517 => 267289, 518 => 268324, 519 => 269361,
+        520 => 270400, 521 => 271441, 522 => 272484, 523 => 273529, 524 => 274576, 525 => 275625, 526 => 276676, 527 => 277729, 528 => 278784, 529 => 279841,
+        530 => 280900, 531 => 281961, 532 => 283024, 533 => 284089, 534 => 285156, 535 => 286225, 536 => 287296, 537 => 288369, 538 => 289444, 539 => 290521,
+        540 => 291600, 541 => 292681, 542 => 293764, 543 => 294849, 544 => 295936, 545 => 297025, 546 => 298116, 547 => 299209, 548 => 300304, 549 => 301401,
+        550 => 302500, 551 => 303601, 552 => 304704, 553 => 305809, 554 => 306916, 555 => 308025, 556 => 309136, 557 => 310249, 558 => 311364, 559 => 312481,
+        560 => 313600, 561 => 314721, 562 => 315844, 563 => 316969, 564 => 318096, 565 => 319225, 566 => 320356, 567 => 321489, 568 => 322624, 569 => 323761,
+        570 => 324900, 571 => 326041, 572 => 327184, 573 => 328329, 574 => 329476, 575 => 330625, 576 => 331776, 577 => 332929, 578 => 334084, 579 => 335241,
+        580 => 336400, 581 => 337561, 582 => 338724, 583 => 339889, 584 => 341056, 585 => 342225, 586 => 343396, 587 => 344569, 588 => 345744, 589 => 346921,
+        590 => 348100, 591 => 349281, 592 => 350464, 593 => 351649, 594 => 352836, 595 => 354025, 596 => 355216, 597 => 356409, 598 => 357604, 599 => 358801,
+        600 => 360000, 601 => 361201, 602 => 362404, 603 => 363609, 604 => 364816, 605 => 366025, 606 => 367236, 607 => 368449, 608 => 369664, 609 => 370881,
+        610 => 372100, 611 => 373321, 612 => 374544, 613 => 375769, 614 => 376996, 615 => 378225, 616 => 379456, 617 => 380689, 618 => 381924, 619 => 383161,
+        620 => 384400, 621 => 385641, 622 => 386884, 623 => 388129, 624 => 389376, 625 => 390625, 626 => 391876, 627 => 393129, 628 => 394384, 629 => 395641,
+        630 => 396900, 631 => 398161, 632 => 399424, 633 => 400689, 634 => 401956, 635 => 403225, 636 => 404496, 637 => 405769, 638 => 407044, 639 => 408321,
+        640 => 409600, 641 => 410881, 642 => 412164, 643 => 413449, 644 => 414736, 645 => 416025, 646 => 417316, 647 => 418609, 648 => 419904, 649 => 421201,
+        650 => 422500, 651 => 423801, 652 => 425104, 653 => 426409, 654 => 427716, 655 => 429025, 656 => 430336, 657 => 431649, 658 => 432964, 659 => 434281,
+        660 => 435600, 661 => 436921, 662 => 438244, 663 => 439569, 664 => 440896, 665 => 442225, 666 => 443556, 667 => 444889, 668 => 446224, 669 => 447561,
+        670 => 448900, 671 => 450241, 672 => 451584, 673 => 452929, 674 => 454276, 675 => 455625, 676 => 456976, 677 => 458329, 678 => 459684, 679 => 461041,
+        680 => 462400, 681 => 463761, 682 => 465124, 683 => 466489, 684 => 467856, 685 => 469225, 686 => 470596, 687 => 471969, 688 => 473344, 689 => 474721,
+        690 => 476100, 691 => 477481, 692 => 478864, 693 => 480249, 694 => 481636, 695 => 483025, 696 => 484416, 697 => 485809, 698 => 487204, 699 => 488601,
+        700 => 490000, 701 => 491401, 702 => 492804, 703 => 494209, 704 => 495616, 705 => 497025, 706 => 498436, 707 => 499849, 708 => 501264, 709 => 502681,
+        710 => 504100, 711 => 505521, 712 => 506944, 713 => 508369, 714 => 509796, 715 => 511225, 716 => 512656, 717 => 514089, 718 => 515524, 719 => 516961,
+        720 => 518400, 721 => 519841, 722 => 521284, 723 => 522729, 724 => 524176, 725 => 525625, 726 => 527076, 727 => 528529, 728 => 529984, 729 => 531441,
+        730 => 532900, 731 => 534361, 732 => 535824, 733 => 537289, 734 => 538756, 735 => 540225, 736 => 541696, 737 => 543169, 738 => 544644, 739 => 546121,
+        740 => 547600, 741 => 549081, 742 => 550564, 743 => 552049, 744 => 553536, 745 => 555025, 746 => 556516, 747 => 558009, 748 => 559504, 749 => 561001,
+        750 => 562500, 751 => 564001, 752 => 565504, 753 => 567009, 754 => 568516, 755 => 570025, 756 => 571536, 757 => 573049, 758 => 574564, 759 => 576081,
+        760 => 577600, 761 => 579121, 762 => 580644, 763 => 582169, 764 => 583696, 765 => 585225, 766 => 586756, 767 => 588289, 768 => 589824, 769 => 591361,
+        770 => 592900, 771 => 594441, 772 => 595984, 773 => 597529, 774 => 599076, 775 => 600625, 776 => 602176, 777 => 603729, 778 => 605284, 779 => 606841,
+        780 => 608400, 781 => 609961, 782 => 611524, 783 => 613089, 784 => 614656, 785 => 616225, 786 => 617796, 787 => 619369, 788 => 620944, 789 => 622521,
+        790 => 624100, 791 => 625681, 792 => 627264, 793 => 628849, 794 => 630436, 795 => 632025, 796 => 633616, 797 => 635209, 798 => 636804, 799 => 638401,
+        800 => 640000, 801 => 641601, 802 => 643204, 803 => 644809, 804 => 646416, 805 => 648025, 806 => 649636, 807 => 651249, 808 => 652864, 809 => 654481,
+        810 => 656100, 811 => 657721, 812 => 659344, 813 => 660969, 814 => 662596, 815 => 664225, 816 => 665856, 817 => 667489, 818 => 669124, 819 => 670761,
+        820 => 672400, 821 => 674041, 822 => 675684, 823 => 677329, 824 => 678976, 825 => 680625, 826 => 682276, 827 => 683929, 828 => 685584, 829 => 687241,
+        830 => 688900, 831 => 690561, 832 => 692224, 833 => 693889, 834 => 695556, 835 => 697225, 836 => 698896, 837 => 700569, 838 => 702244, 839 => 703921,
+        840 => 705600, 841 => 707281, 842 => 708964, 843 => 710649, 844 => 712336, 845 => 714025, 846 => 715716, 847 => 717409, 848 => 719104, 849 => 720801,
+        850 => 722500, 851 => 724201, 852 => 725904, 853 => 727609, 854 => 729316, 855 => 731025, 856 => 732736, 857 => 734449, 858 => 736164, 859 => 737881,
+        860 => 739600, 861 => 741321, 862 => 743044, 863 => 744769, 864 => 746496, 865 => 748225, 866 => 749956, 867 => 751689, 868 => 753424, 869 => 755161,
+        870 => 756900, 871 => 758641, 872 => 760384, 873 => 762129, 874 => 763876, 875 => 765625, 876 => 767376, 877 => 769129, 878 => 770884, 879 => 772641,
+        880 => 774400, 881 => 776161, 882 => 777924, 883 => 779689, 884 => 781456, 885 => 783225, 886 => 784996, 887 => 786769, 888 => 788544, 889 => 790321,
+        890 => 792100, 891 => 793881, 892 => 795664, 893 => 797449, 894 => 799236, 895 => 801025, 896 => 802816, 897 => 804609, 898 => 806404, 899 => 808201,
+        900 => 810000, 901 => 811801, 902 => 813604, 903 => 815409, 904 => 817216, 905 => 819025, 906 => 820836, 907 => 822649, 908 => 824464, 909 => 826281,
+        910 => 828100, 911 => 829921, 912 => 831744, 913 => 833569, 914 => 835396, 915 => 837225, 916 => 839056, 917 => 840889, 918 => 842724, 919 => 844561,
+        920 => 846400, 921 => 848241, 922 => 850084, 923 => 851929, 924 => 853776, 925 => 855625, 926 => 857476, 927 => 859329, 928 => 861184, 929 => 863041,
+        930 => 864900, 931 => 866761, 932 => 868624, 933 => 870489, 934 => 872356, 935 => 874225, 936 => 876096, 937 => 877969, 938 => 879844, 939 => 881721,
+        940 => 883600, 941 => 885481, 942 => 887364, 943 => 889249, 944 => 891136, 945 => 893025, 946 => 894916, 947 => 896809, 948 => 898704, 949 => 900601,
+        950 => 902500, 951 => 904401, 952 => 906304, 953 => 908209, 954 => 910116, 955 => 912025, 956 => 913936, 957 => 915849, 958 => 917764, 959 => 919681,
+        960 => 921600, 961 => 923521, 962 => 925444, 963 => 927369, 964 => 929296, 965 => 931225, 966 => 933156, 967 => 935089, 968 => 937024, 969 => 938961,
+        970 => 940900, 971 => 942841, 972 => 944784, 973 => 946729, 974 => 948676, 975 => 950625, 976 => 952576, 977 => 954529, 978 => 956484, 979 => 958441,
+        980 => 960400, 981 => 962361, 982 => 964324, 983 => 966289, 984 => 968256, 985 => 970225, 986 => 972196, 987 => 974169, 988 => 976144, 989 => 978121,
+        990 => 980100, 991 => 982081, 992 => 984064, 993 => 986049, 994 => 988036, 995 => 990025, 996 => 992016, 997 => 994009, 998 => 996004, 999 => 998001,
+    ];
+    assert_eq!(m.len(), 1000);
+    assert_eq!(m[&999], 999 * 999);
+}
+
+#[test]
+fn test_cloned() {
+    let xs: &[i32] = &[1, 2, 3];
+    let v = collect![cloned as Vec<i32>: ..xs];
+    assert_eq!(v, [1, 2, 3]);
+
+    let v = collect![cloned as Vec<i32>: 0, ..xs, 4, ..&xs[..1]];
+    assert_eq!(v, [0, 1, 2, 3, 4, 1]);
+    assert_eq!(v.capacity(), 6);
+
+    let names = vec![String::from("a"), String::from("b")];
+    let v = collect![cloned as Vec<String>: ..&names];
+    assert_eq!(v, names);
+}
+
+#[test]
+fn test_spread_defaults() {
+    // Spreading an inline `collect!` is how to start from a set of defaults, then override some of them.
+    let m: HashMap<&str, i32> = collect![..collect![as HashMap<_, _>: "a" => 0, "b" => 0], "a" => 5];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 5, "b" => 0]);
+
+    let m = collect![as BTreeMap<_, _>: ..collect![as BTreeMap<_, _>: 1 => 'x', 2 => 'y'], 2 => 'z', 3 => 'w'];
+    assert_eq!(m, collect![1 => 'x', 2 => 'z', 3 => 'w']);
+}
+
+#[test]
+fn test_from() {
+    let pairs = vec![("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)];
+    let m = collect![from pairs as HashMap<_, _>];
+    assert_eq!(m, collect![as HashMap<_, _>: "a" => 1, "b" => 2, "c" => 3, "d" => 4, "e" => 5]);
+
+    // The map should have allocated exactly once, for exactly the number of pairs.
+    let expected: HashMap<&str, i32> = HashMap::with_capacity(5);
+    assert_eq!(m.capacity(), expected.capacity());
+
+    let v: Vec<i32> = collect![from 0..3];
+    assert_eq!(v, [0, 1, 2]);
+
+    let m = collect![from (m.into_iter().filter(|&(_, v)| v > 3)) as BTreeMap<_, _>];
+    assert_eq!(m, collect!["d" => 4, "e" => 5]);
+}
+
+#[test]
+fn test_limit() {
+    let v = collect![limit 1000 as Vec<_>: ..std::iter::repeat(1)];
+    assert_eq!(v.len(), 1000);
+    assert_eq!(v.capacity(), 1000);
+
+    // Each spread is limited separately; plain elements aren't affected.
+    let v = collect![limit 2 as Vec<_>: 0, ..1..10, 0, ..vec![5]];
+    assert_eq!(v, [0, 1, 2, 0, 5]);
+
+    let xs = [1, 2, 3];
+    let v = collect![limit (1 + 1) cloned as Vec<i32>: ..&xs];
+    assert_eq!(v, [1, 2]);
+
+    let m = collect![limit 2 as BTreeMap<_, _>: ..(0..).map(|i| (i, i * i))];
+    assert_eq!(m, collect![0 => 0, 1 => 1]);
+
+    // A variable called `limit` is still just an element.
+    let limit = 300u32;
+    let v: Vec<u8> = collect![limit as u8, 2];
+    assert_eq!(v, [44, 2]);
+    let v: Vec<u32> = collect![limit.min(7), limit + 1];
+    assert_eq!(v, [7, 301]);
+    let m: HashMap<u8, i32> = collect![limit as u8 => 1];
+    assert_eq!(m[&44], 1);
+}
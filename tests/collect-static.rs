@@ -0,0 +1,33 @@
+/*
+Copyright ⓒ 2015 Daniel Keep.
+
+Licensed under the MIT license (see LICENSE or <http://opensource.org
+/licenses/MIT>) or the Apache License, Version 2.0 (see LICENSE of
+<http://www.apache.org/licenses/LICENSE-2.0>), at your option. All
+files in the project carrying such notice may not be copied, modified,
+or distributed except according to those terms.
+*/
+/*!
+This test makes sure that `static_map!` defines lazily-built statics.  It only builds with `--features once_cell`.
+*/
+#![cfg(feature = "once_cell")]
+
+#[macro_use] extern crate collect_mac;
+
+use std::collections::{BTreeMap, HashMap};
+
+static_map!(CODES: HashMap<&'static str, u16> = { "ok" => 200, "not found" => 404, });
+
+static_map!(
+    /// Squares, in order.
+    pub SQUARES: BTreeMap<u32, u32> = { 1 => 1, ..(2..4).map(|i| (i, i * i)) }
+);
+
+#[test]
+fn test_static_map() {
+    assert_eq!(CODES.len(), 2);
+    assert_eq!(CODES["ok"], 200);
+    assert_eq!(CODES.get("teapot"), None);
+
+    assert_eq!(SQUARES.values().cloned().collect::<Vec<_>>(), [1, 4, 9]);
+}
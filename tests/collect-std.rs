@@ -21,6 +21,7 @@ use std::collections::{
     LinkedList,
     VecDeque,
 };
+use std::hash::{BuildHasherDefault, Hasher};
 
 /**
 Check that two collections are equal by popping from them.
@@ -74,7 +75,7 @@ macro_rules! check_growth {
 
     (
         ty: $col_ty:ty,
-        es: $es:tt,
+        es: [$($es:expr),* $(,)*],
         eq: $eq:expr,
         cmp: $cmp:ident,
     ) => {
@@ -84,19 +85,17 @@ macro_rules! check_growth {
             let col = collect!(
                 @collect
                 ty: $col_ty,
-                es: $es,
+                es: [$($es),*],
                 cb: (col) { caps.push(col.capacity()); },
             );
 
-            // Ensure that the collection is correct *and* the capacity goes: `[init_cap, final_cap, ...]`.
-            let init_cap = <$col_ty>::new().capacity();
-            let final_cap = col.capacity();
+            // Ensure that the collection is correct *and* the capacity goes: `[init_cap, final_cap]`, where `final_cap` is exactly what reserving space for every element up front gives.  Anything larger means the collection grew more than once.
+            let mut expected = <$col_ty as Default>::default();
+            let init_cap = expected.capacity();
+            expected.reserve([$(stringify!($es)),*].len());
+            let final_cap = expected.capacity();
 
-            assert_eq!(("caps[0]", caps[0]), ("caps[0]", init_cap));
-            assert_eq!(
-                ("caps[1..]", &caps[1..]),
-                ("caps[1..]", &*vec![final_cap; col.len()])
-            );
+            assert_eq!(("caps", &caps[..]), ("caps", &[init_cap, final_cap][..]));
 
             $cmp!(col, $eq);
         }
@@ -150,6 +149,29 @@ fn test_collect() {
     drop(f);
 }
 
+#[test]
+fn test_evaluation_order() {
+    use std::cell::RefCell;
+
+    // Every element expression should be evaluated exactly once, from left to right.
+    let log = RefCell::new(vec![]);
+    let step = |i: i32| { log.borrow_mut().push(i); i };
+
+    let v: Vec<i32> = collect![step(0), step(1), step(2), step(3), step(4), step(5)];
+    assert_eq!(v, [0, 1, 2, 3, 4, 5]);
+    assert_eq!(*log.borrow(), [0, 1, 2, 3, 4, 5]);
+
+    log.borrow_mut().clear();
+    let m: BTreeMap<i32, i32> = collect![step(0) => step(1), step(2) => step(3)];
+    assert_eq!(m.len(), 2);
+    assert_eq!(*log.borrow(), [0, 1, 2, 3]);
+
+    log.borrow_mut().clear();
+    let v = collect![as Vec<i32>: step(0), ..vec![step(1), step(2)], step(3)];
+    assert_eq!(v, [0, 1, 2, 3]);
+    assert_eq!(*log.borrow(), [0, 1, 2, 3]);
+}
+
 #[test]
 fn test_binary_heap() {
     let _: BinaryHeap<i32> = collect![];
@@ -233,6 +255,41 @@ fn test_hash_map() {
     );
 }
 
+#[test]
+fn test_hash_map_with_hasher() {
+    /// A deliberately simple hasher, just to have something other than `RandomState`.
+    #[derive(Default)]
+    struct XorHasher(u64);
+
+    impl Hasher for XorHasher {
+        fn finish(&self) -> u64 {
+            self.0
+        }
+
+        fn write(&mut self, bytes: &[u8]) {
+            for &b in bytes {
+                self.0 = self.0.rotate_left(8) ^ u64::from(b);
+            }
+        }
+    }
+
+    type Xor = BuildHasherDefault<XorHasher>;
+    type Sstr = &'static str;
+
+    let _: HashMap<Sstr, i32, Xor> = collect![];
+    check_is!(HashMap<Sstr, i32, Xor>: collect![as HashMap<Sstr, i32, Xor>]);
+    check_is!(HashMap<Sstr, i32, Xor>: collect![as HashMap<Sstr, i32, Xor>:]);
+
+    let m = check_is!(HashMap<Sstr, i32, Xor>: collect![as HashMap<_, _, Xor>: "hi" => 2, "lo" => 3]);
+    assert_eq!((m.len(), m["hi"], m["lo"]), (2, 2, 3));
+
+    check_growth!(
+        ty: HashMap<Sstr, i32, Xor>,
+        es: [("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)],
+        eq: vec![("a", 1), ("b", 2), ("c", 3), ("d", 4), ("e", 5)].into_iter().collect::<HashMap<_, _, Xor>>(),
+    );
+}
+
 #[test]
 fn test_hash_set() {
     let _: HashSet<i32> = collect![];
@@ -327,5 +384,59 @@ fn test_vec_deque() {
 }
 
 fn check_is<T: Any, U: Any>(v: &U) {
-    assert!(Any::is::<T>(v));
+    assert!(<dyn Any>::is::<T>(v));
+}
+
+#[test]
+fn test_shared_slices() {
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let xs = collect![as Arc<[i32]>: 1, 2, 3];
+    assert_eq!(&xs[..], &[1, 2, 3]);
+    assert_eq!(Arc::strong_count(&xs), 1);
+
+    let xs = collect![as std::rc::Rc<[String]>: String::from("a"), String::from("b")];
+    assert_eq!(&xs[..], &[String::from("a"), String::from("b")]);
+    assert_eq!(Rc::strong_count(&xs), 1);
+
+    let xs = collect![as Box<[_]>: 1u8, ..vec![2, 3]];
+    assert_eq!(&xs[..], &[1, 2, 3]);
+
+    let xs = collect![as Rc<[i32]>];
+    assert!(xs.is_empty());
+
+    // Arrays as element types still go through the usual path.
+    let xs = collect![as Vec<[u8; 2]>: [1, 2], [3, 4]];
+    assert_eq!(xs, vec![[1, 2], [3, 4]]);
+}
+
+#[test]
+fn test_arrays() {
+    const N: usize = 2;
+
+    assert_eq!(collect![as [i32; 3]: 1, 2, 3], [1, 2, 3]);
+    assert_eq!(collect![as [i32; 2 + 1]: 1, 2, 3,], [1, 2, 3]);
+    assert_eq!(collect![as [&str; N * 2]: "a", "b", "c", "d"], ["a", "b", "c", "d"]);
+    assert_eq!(collect![as [u8; N - 2]], []);
+}
+
+#[test]
+fn test_btree_bulk() {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    let s = collect![as BTreeSet<_>: 5, 3, 1, 4, 2, 3, 5];
+    assert_eq!(s.into_iter().collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+    // The last of several equal keys wins, just as with `insert`.
+    let m = collect![as BTreeMap<_, _>: 2 => "b", 1 => "a", 2 => "B"];
+    assert_eq!(m.into_iter().collect::<Vec<_>>(), [(1, "a"), (2, "B")]);
+
+    // Spreads still work, through the usual path.
+    let s = collect![as BTreeSet<i32>: 3, ..vec![1, 2], 1];
+    assert_eq!(s.into_iter().collect::<Vec<_>>(), [1, 2, 3]);
+
+    let m = collect![keys as BTreeMap<_, _>: "x"];
+    let _: &BTreeMap<&str, i32> = &m;
+    assert_eq!(m["x"], 0);
 }